@@ -0,0 +1,29 @@
+// Records how the benchmarks were compiled so that the runner can report it next to the results.
+
+#![allow(non_snake_case)]
+
+use std::env;
+use std::process::Command;
+
+fn main()
+{
+	let rustc: String = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+	let rustcVersion: String = Command::new(rustc)
+		.arg("--version")
+		.output()
+		.ok()
+		.and_then(|output| String::from_utf8(output.stdout).ok())
+		.map(|version| String::from(version.trim()))
+		.unwrap_or_else(|| String::from("unknown"));
+
+	// CARGO_ENCODED_RUSTFLAGS separates flags with 0x1f.
+	let rustflags: String = env::var("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default().replace('\x1f', " ");
+
+	println!("cargo:rustc-env=BENCHMARKS_RUSTC_VERSION={}", rustcVersion);
+	println!("cargo:rustc-env=BENCHMARKS_PROFILE={}", env::var("PROFILE").unwrap_or_default());
+	println!("cargo:rustc-env=BENCHMARKS_OPT_LEVEL={}", env::var("OPT_LEVEL").unwrap_or_default());
+	println!("cargo:rustc-env=BENCHMARKS_TARGET={}", env::var("TARGET").unwrap_or_default());
+	println!("cargo:rustc-env=BENCHMARKS_TARGET_FEATURES={}", env::var("CARGO_CFG_TARGET_FEATURE").unwrap_or_default());
+	println!("cargo:rustc-env=BENCHMARKS_RUSTFLAGS={}", rustflags);
+	println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,74 @@
+// The values native_lib/benchmarks.c built with gcc 12 -O3 returns for the workloads in
+// workloads/full.toml and workloads/smoke.toml. The runner validates its results against this table
+// and tests/golden.rs checks the kernels with it, so there is only one copy.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GoldenValue
+{
+	U32(u32),
+	I32(i32),
+	U64(u64),
+	F32(f32),
+	F64(f64)
+}
+
+// A Rust port that is known to compute something other than the C version.
+pub struct Divergence
+{
+	// What the Rust port returns for the same parameters.
+	pub value: GoldenValue,
+	pub reason: &'static str
+}
+
+pub struct GoldenChecksum
+{
+	// The exported function, e.g. "benchmark_nbody".
+	pub symbol: &'static str,
+	pub parameters: &'static [u32],
+	pub expected: GoldenValue,
+	pub rustDivergence: Option<Divergence>
+}
+
+const radixDivergence: &str = "the Rust port's counting sort never decrements the bucket counts, so the head of the array stays 0";
+const nbodyDivergence: &str = "the Rust port advances the bodies once more than the C version, which counts down before testing";
+
+const fn golden(symbol: &'static str, parameters: &'static [u32], expected: GoldenValue) -> GoldenChecksum
+{
+	return GoldenChecksum { symbol, parameters, expected, rustDivergence: None };
+}
+
+const fn diverging(symbol: &'static str, parameters: &'static [u32], expected: GoldenValue, value: GoldenValue, reason: &'static str) -> GoldenChecksum
+{
+	return GoldenChecksum { symbol, parameters, expected, rustDivergence: Some(Divergence { value, reason }) };
+}
+
+pub const goldenChecksums: [GoldenChecksum; 22] = [
+	golden("benchmark_fibonacci", &[46], GoldenValue::U32(2971215073)),
+	golden("benchmark_mandelbrot", &[1920, 1080, 8], GoldenValue::F32(4.1998944)),
+	diverging("benchmark_nbody", &[100000000], GoldenValue::F64(7.978854149829663e-5), GoldenValue::F64(8.992732064656839e-5), nbodyDivergence),
+	golden("benchmark_sieve_of_eratosthenes", &[1000000], GoldenValue::U32(308)),
+	golden("benchmark_pixar_raytracer", &[720, 480, 16], GoldenValue::F32(741.63477)),
+	golden("benchmark_fireflies_flocking", &[1000, 1000], GoldenValue::F32(1015115300.0)),
+	golden("benchmark_polynomials", &[10000000], GoldenValue::F32(21677722.0)),
+	golden("benchmark_particle_kinematics", &[1000, 10000000], GoldenValue::F32(64407596.0)),
+	golden("benchmark_arcfour", &[10000000], GoldenValue::I32(10)),
+	golden("benchmark_seahash", &[1000000], GoldenValue::U64(11468720252350332299)),
+	diverging("benchmark_radix", &[1000000], GoldenValue::I32(668), GoldenValue::I32(0), radixDivergence),
+
+	golden("benchmark_fibonacci", &[25], GoldenValue::U32(121393)),
+	golden("benchmark_mandelbrot", &[192, 108, 8], GoldenValue::F32(4.110409)),
+	diverging("benchmark_nbody", &[10000], GoldenValue::F64(0.009135699168192925), GoldenValue::F64(0.00917877122268626), nbodyDivergence),
+	golden("benchmark_sieve_of_eratosthenes", &[100], GoldenValue::U32(308)),
+	golden("benchmark_pixar_raytracer", &[72, 48, 1], GoldenValue::F32(762.17584)),
+	golden("benchmark_fireflies_flocking", &[100, 100], GoldenValue::F32(1666318600.0)),
+	golden("benchmark_polynomials", &[1000], GoldenValue::F32(2500.0)),
+	golden("benchmark_particle_kinematics", &[100, 1000], GoldenValue::F32(6003.0)),
+	golden("benchmark_arcfour", &[1000], GoldenValue::I32(10)),
+	golden("benchmark_seahash", &[100], GoldenValue::U64(11468720252350332299)),
+	diverging("benchmark_radix", &[100], GoldenValue::I32(160), GoldenValue::I32(0), radixDivergence)
+];
+
+pub fn find_golden(symbol: &str, parameters: &[u32]) -> Option<&'static GoldenChecksum>
+{
+	return goldenChecksums.iter().find(|golden| golden.symbol == symbol && golden.parameters == parameters);
+}
@@ -4,7 +4,19 @@
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
 #![allow(unused_parens)]
+#![allow(unused_assignments)]
+#![allow(unused_imports)]
+#![allow(unused_mut)]
+#![allow(unused_variables)]
+#![allow(clippy::approx_constant)]
+#![allow(clippy::assign_op_pattern)]
+#![allow(clippy::double_parens)]
+#![allow(clippy::excessive_precision)]
+#![allow(clippy::ineffective_open_options)]
+#![allow(clippy::needless_range_loop)]
+#![allow(clippy::needless_return)]
 
-mod i32_benchmarks;
-mod f32_benchmarks;
-mod f64_benchmarks;
+pub mod i32_benchmarks;
+pub mod f32_benchmarks;
+pub mod f64_benchmarks;
+pub mod golden;
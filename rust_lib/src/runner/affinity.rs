@@ -0,0 +1,391 @@
+// Keeps the benchmark on one CPU core, as the README asks for, and optionally raises its priority.
+// Benchmarks.cs can only raise ProcessPriorityClass; on Linux the runner can also pin the thread with
+// sched_setaffinity and read the core's SMT siblings and isolation from sysfs.
+
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+// The chosen CPU or a sibling busier than this while the runner starts gets a warning.
+const busyThreshold: f64 = 0.10;
+const busySampleInterval: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum SchedulerPolicy
+{
+	Other,
+	Batch,
+	Idle,
+	Fifo,
+	RoundRobin
+}
+
+impl SchedulerPolicy
+{
+	pub(crate) fn from_name(name: &str) -> Option<SchedulerPolicy>
+	{
+		return match name
+		{
+			"other" => Some(SchedulerPolicy::Other),
+			"batch" => Some(SchedulerPolicy::Batch),
+			"idle" => Some(SchedulerPolicy::Idle),
+			"fifo" => Some(SchedulerPolicy::Fifo),
+			"rr" => Some(SchedulerPolicy::RoundRobin),
+			_ => None
+		};
+	}
+
+	pub(crate) fn as_str(self) -> &'static str
+	{
+		return match self
+		{
+			SchedulerPolicy::Other => "other",
+			SchedulerPolicy::Batch => "batch",
+			SchedulerPolicy::Idle => "idle",
+			SchedulerPolicy::Fifo => "fifo",
+			SchedulerPolicy::RoundRobin => "rr"
+		};
+	}
+
+	// The SCHED_* constants from <sched.h>.
+	#[cfg(target_os = "linux")]
+	fn to_linux(self) -> i32
+	{
+		return match self
+		{
+			SchedulerPolicy::Other => 0,
+			SchedulerPolicy::Fifo => 1,
+			SchedulerPolicy::RoundRobin => 2,
+			SchedulerPolicy::Batch => 3,
+			SchedulerPolicy::Idle => 5
+		};
+	}
+
+	#[cfg(target_os = "linux")]
+	fn from_linux(policy: i32) -> Option<SchedulerPolicy>
+	{
+		return [SchedulerPolicy::Other, SchedulerPolicy::Fifo, SchedulerPolicy::RoundRobin, SchedulerPolicy::Batch, SchedulerPolicy::Idle]
+			.into_iter()
+			.find(|candidate| candidate.to_linux() == policy);
+	}
+}
+
+#[cfg(target_os = "linux")]
+mod ffi
+{
+	// glibc's cpu_set_t holds 1024 CPUs.
+	pub(super) const cpuSetWords: usize = 16;
+
+	#[repr(C)]
+	pub(super) struct SchedParam
+	{
+		pub(super) sched_priority: i32
+	}
+
+	extern "C"
+	{
+		pub(super) fn sched_setaffinity(pid: i32, cpusetsize: usize, mask: *const u64) -> i32;
+		pub(super) fn sched_getaffinity(pid: i32, cpusetsize: usize, mask: *mut u64) -> i32;
+		pub(super) fn sched_setscheduler(pid: i32, policy: i32, param: *const SchedParam) -> i32;
+		pub(super) fn sched_getscheduler(pid: i32) -> i32;
+		pub(super) fn setpriority(which: i32, who: u32, prio: i32) -> i32;
+		pub(super) fn getpriority(which: i32, who: u32) -> i32;
+	}
+
+	pub(super) const PRIO_PROCESS: i32 = 0;
+}
+
+// Parses the "0-3,8,10-11" lists used throughout /sys/devices/system/cpu.
+pub(crate) fn parse_cpu_list(text: &str) -> Vec<usize>
+{
+	let mut cpus: Vec<usize> = Vec::new();
+
+	for range in text.trim().split(',').filter(|range| !range.is_empty())
+	{
+		let (first, last) = range.split_once('-').unwrap_or((range, range));
+
+		if let (Ok(first), Ok(last)) = (first.parse::<usize>(), last.parse::<usize>())
+		{
+			cpus.extend(first..=last);
+		}
+	}
+
+	return cpus;
+}
+
+// The reverse of parse_cpu_list, collapsing consecutive CPUs into ranges.
+pub(crate) fn format_cpu_list(cpus: &[usize]) -> String
+{
+	let mut ranges: Vec<String> = Vec::new();
+	let mut i: usize = 0;
+
+	while (i < cpus.len())
+	{
+		let mut j: usize = i;
+		while (j + 1 < cpus.len() && cpus[j + 1] == cpus[j] + 1)
+		{
+			j += 1;
+		}
+
+		ranges.push(if (j > i) { format!("{}-{}", cpus[i], cpus[j]) } else { cpus[i].to_string() });
+		i = j + 1;
+	}
+
+	return ranges.join(",");
+}
+
+fn read_cpu_list(path: &str) -> Option<Vec<usize>>
+{
+	return fs::read_to_string(path).ok().map(|text| parse_cpu_list(&text));
+}
+
+// Logical CPUs that share a physical core with the given one, including itself.
+fn smt_siblings(cpu: usize) -> Vec<usize>
+{
+	return read_cpu_list(&format!("/sys/devices/system/cpu/cpu{}/topology/thread_siblings_list", cpu)).unwrap_or_else(|| vec![cpu]);
+}
+
+// (busy, total) jiffies per CPU from /proc/stat. Idle and iowait count as not busy. Only the first
+// eight fields are summed: guest and guest_nice are already included in user and nice.
+fn cpu_times() -> Vec<(usize, u64, u64)>
+{
+	let stat: String = fs::read_to_string("/proc/stat").unwrap_or_default();
+	let mut times: Vec<(usize, u64, u64)> = Vec::new();
+
+	for line in stat.lines()
+	{
+		let mut fields = line.split_whitespace();
+		let Some(cpu) = fields.next().and_then(|name| name.strip_prefix("cpu")).and_then(|number| number.parse::<usize>().ok()) else { continue };
+		let values: Vec<u64> = fields.take(8).filter_map(|field| field.parse::<u64>().ok()).collect();
+
+		if (values.len() >= 5)
+		{
+			let total: u64 = values.iter().sum();
+			times.push((cpu, total - values[3] - values[4], total));
+		}
+	}
+
+	return times;
+}
+
+// Fraction of the time each CPU was busy over a short interval.
+fn busy_fractions() -> Vec<(usize, f64)>
+{
+	let before: Vec<(usize, u64, u64)> = cpu_times();
+	thread::sleep(busySampleInterval);
+	let after: Vec<(usize, u64, u64)> = cpu_times();
+
+	return after
+		.iter()
+		.filter_map(|(cpu, busy, total)| {
+			let (_, busyBefore, totalBefore) = before.iter().find(|(other, _, _)| other == cpu)?;
+			let elapsed: u64 = total.saturating_sub(*totalBefore);
+
+			if (elapsed == 0)
+			{
+				return None;
+			}
+
+			Some((*cpu, busy.saturating_sub(*busyBefore) as f64 / elapsed as f64))
+		})
+		.collect();
+}
+
+// Describes the chosen core and warns about anything that will disturb it: other work on the core
+// itself or on its SMT siblings, and not being in the kernel's isolcpus list when other CPUs are.
+pub(crate) fn describe_cpu(cpu: usize) -> (String, Vec<String>)
+{
+	let siblings: Vec<usize> = smt_siblings(cpu);
+	let isolated: Vec<usize> = read_cpu_list("/sys/devices/system/cpu/isolated").unwrap_or_default();
+	let mut warnings: Vec<String> = Vec::new();
+
+	let description: String = format!(
+		"Pinned to CPU {} (SMT siblings {}, {})",
+		cpu,
+		format_cpu_list(&siblings),
+		if (isolated.contains(&cpu)) { "isolated" } else { "not isolated" }
+	);
+
+	if (!isolated.is_empty() && !isolated.contains(&cpu))
+	{
+		warnings.push(format!("Warning: CPU {} is not isolated, but CPUs {} are", cpu, format_cpu_list(&isolated)));
+	}
+
+	for (other, busy) in busy_fractions().into_iter().filter(|(other, busy)| siblings.contains(other) && *busy > busyThreshold)
+	{
+		if (other == cpu)
+		{
+			warnings.push(format!("Warning: CPU {} was {:.0}% busy with other work. Timings will be noisy", cpu, busy * 100.0));
+		}
+		else
+		{
+			warnings.push(format!(
+				"Warning: CPU {} shares a physical core with CPU {}, which was {:.0}% busy. Timings will be noisy",
+				cpu,
+				other,
+				busy * 100.0
+			));
+		}
+	}
+
+	return (description, warnings);
+}
+
+#[cfg(target_os = "linux")]
+fn os_error(action: &str) -> String
+{
+	return format!("Could not {}: {}", action, std::io::Error::last_os_error());
+}
+
+// Pins the calling thread, and any process it starts later, to one CPU.
+#[cfg(target_os = "linux")]
+pub(crate) fn pin_to_cpu(cpu: usize) -> Result<(), String>
+{
+	if (cpu >= ffi::cpuSetWords * 64)
+	{
+		return Err(format!("CPU {} is out of range", cpu));
+	}
+
+	if let Some(online) = read_cpu_list("/sys/devices/system/cpu/online")
+	{
+		if (!online.contains(&cpu))
+		{
+			return Err(format!("CPU {} is not online. Online CPUs are {}", cpu, format_cpu_list(&online)));
+		}
+	}
+
+	let mut mask: [u64; ffi::cpuSetWords] = [0; ffi::cpuSetWords];
+	mask[cpu / 64] |= 1 << (cpu % 64);
+
+	if (unsafe { ffi::sched_setaffinity(0, std::mem::size_of_val(&mask), mask.as_ptr()) } != 0)
+	{
+		return Err(os_error(&format!("pin to CPU {}", cpu)));
+	}
+
+	return Ok(());
+}
+
+// Lets the calling thread run on every online CPU except the given one, so that a helper thread stays
+// off the benchmark's core even in a process that was already pinned to it. Fails when no other CPU
+// is online.
+#[cfg(target_os = "linux")]
+pub(crate) fn avoid_cpu(cpu: usize) -> Result<(), String>
+{
+	let others: Vec<usize> = read_cpu_list("/sys/devices/system/cpu/online")
+		.unwrap_or_default()
+		.into_iter()
+		.filter(|other| *other != cpu && *other < ffi::cpuSetWords * 64)
+		.collect();
+
+	if (others.is_empty())
+	{
+		return Err(format!("CPU {} is the only online CPU", cpu));
+	}
+
+	let mut mask: [u64; ffi::cpuSetWords] = [0; ffi::cpuSetWords];
+	for other in others
+	{
+		mask[other / 64] |= 1 << (other % 64);
+	}
+
+	if (unsafe { ffi::sched_setaffinity(0, std::mem::size_of_val(&mask), mask.as_ptr()) } != 0)
+	{
+		return Err(os_error(&format!("move off CPU {}", cpu)));
+	}
+
+	return Ok(());
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn current_affinity() -> Vec<usize>
+{
+	let mut mask: [u64; ffi::cpuSetWords] = [0; ffi::cpuSetWords];
+
+	if (unsafe { ffi::sched_getaffinity(0, std::mem::size_of_val(&mask), mask.as_mut_ptr()) } != 0)
+	{
+		return Vec::new();
+	}
+
+	return (0..ffi::cpuSetWords * 64).filter(|cpu| mask[cpu / 64] & (1 << (cpu % 64)) != 0).collect();
+}
+
+// Negative values need CAP_SYS_NICE or a suitable RLIMIT_NICE.
+#[cfg(target_os = "linux")]
+pub(crate) fn set_nice(nice: i32) -> Result<(), String>
+{
+	if (unsafe { ffi::setpriority(ffi::PRIO_PROCESS, 0, nice) } != 0)
+	{
+		return Err(os_error(&format!("set the nice value to {}", nice)));
+	}
+
+	return Ok(());
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn current_nice() -> Option<i32>
+{
+	return Some(unsafe { ffi::getpriority(ffi::PRIO_PROCESS, 0) });
+}
+
+// The real-time policies run at priority 1, which is already above every normal task.
+#[cfg(target_os = "linux")]
+pub(crate) fn set_scheduler(policy: SchedulerPolicy) -> Result<(), String>
+{
+	let realtime: bool = matches!(policy, SchedulerPolicy::Fifo | SchedulerPolicy::RoundRobin);
+	let parameter: ffi::SchedParam = ffi::SchedParam { sched_priority: if (realtime) { 1 } else { 0 } };
+
+	if (unsafe { ffi::sched_setscheduler(0, policy.to_linux(), &parameter) } != 0)
+	{
+		return Err(os_error(&format!("switch to the {} scheduler", policy.as_str())));
+	}
+
+	return Ok(());
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn current_scheduler() -> Option<SchedulerPolicy>
+{
+	return SchedulerPolicy::from_linux(unsafe { ffi::sched_getscheduler(0) });
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn pin_to_cpu(_cpu: usize) -> Result<(), String>
+{
+	return Err(String::from("--cpu is only supported on Linux"));
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn avoid_cpu(_cpu: usize) -> Result<(), String>
+{
+	return Err(String::from("CPU affinity is only supported on Linux"));
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn current_affinity() -> Vec<usize>
+{
+	return Vec::new();
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn set_nice(_nice: i32) -> Result<(), String>
+{
+	return Err(String::from("--nice is only supported on Linux"));
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn current_nice() -> Option<i32>
+{
+	return None;
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn set_scheduler(_policy: SchedulerPolicy) -> Result<(), String>
+{
+	return Err(String::from("--scheduler is only supported on Linux"));
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn current_scheduler() -> Option<SchedulerPolicy>
+{
+	return None;
+}
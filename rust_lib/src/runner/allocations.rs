@@ -0,0 +1,113 @@
+// Heap usage of the Rust kernels. The runner's global allocator forwards to the system allocator
+// and, while tracking is on, counts what passes through it, so that time spent in malloc can be told
+// apart from compute. Libraries loaded with dlopen have their own allocator, so this only sees the
+// kernels linked into the runner.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+
+pub(crate) struct CountingAllocator;
+
+static tracking: AtomicBool = AtomicBool::new(false);
+static allocationCount: AtomicU64 = AtomicU64::new(0);
+static allocatedBytes: AtomicU64 = AtomicU64::new(0);
+// Relative to when tracking started, so memory allocated before then and freed during a run makes
+// it negative.
+static currentBytes: AtomicI64 = AtomicI64::new(0);
+static peakBytes: AtomicI64 = AtomicI64::new(0);
+
+// What one kernel run allocated.
+#[derive(Clone, Copy, Default, Debug)]
+pub(crate) struct AllocationSample
+{
+	pub(crate) count: u64,
+	pub(crate) bytes: u64,
+	// Highest heap usage above what was live when the run started.
+	pub(crate) peakBytes: u64
+}
+
+fn record_allocation(size: usize)
+{
+	if (!tracking.load(Ordering::Relaxed))
+	{
+		return;
+	}
+
+	allocationCount.fetch_add(1, Ordering::Relaxed);
+	allocatedBytes.fetch_add(size as u64, Ordering::Relaxed);
+	let current: i64 = currentBytes.fetch_add(size as i64, Ordering::Relaxed) + size as i64;
+	peakBytes.fetch_max(current, Ordering::Relaxed);
+}
+
+fn record_release(size: usize)
+{
+	if (tracking.load(Ordering::Relaxed))
+	{
+		currentBytes.fetch_sub(size as i64, Ordering::Relaxed);
+	}
+}
+
+unsafe impl GlobalAlloc for CountingAllocator
+{
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8
+	{
+		let pointer: *mut u8 = System.alloc(layout);
+		if (!pointer.is_null())
+		{
+			record_allocation(layout.size());
+		}
+
+		return pointer;
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8
+	{
+		let pointer: *mut u8 = System.alloc_zeroed(layout);
+		if (!pointer.is_null())
+		{
+			record_allocation(layout.size());
+		}
+
+		return pointer;
+	}
+
+	unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout)
+	{
+		System.dealloc(pointer, layout);
+		record_release(layout.size());
+	}
+
+	// A reallocation counts as a new allocation of the new size and the release of the old one.
+	unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, newSize: usize) -> *mut u8
+	{
+		let newPointer: *mut u8 = System.realloc(pointer, layout, newSize);
+		if (!newPointer.is_null())
+		{
+			record_release(layout.size());
+			record_allocation(newSize);
+		}
+
+		return newPointer;
+	}
+}
+
+pub(crate) fn start_tracking()
+{
+	allocationCount.store(0, Ordering::Relaxed);
+	allocatedBytes.store(0, Ordering::Relaxed);
+	currentBytes.store(0, Ordering::Relaxed);
+	peakBytes.store(0, Ordering::Relaxed);
+	tracking.store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn stop_tracking() -> AllocationSample
+{
+	tracking.store(false, Ordering::Relaxed);
+
+	return AllocationSample
+	{
+		count: allocationCount.load(Ordering::Relaxed),
+		bytes: allocatedBytes.load(Ordering::Relaxed),
+		peakBytes: peakBytes.load(Ordering::Relaxed).max(0) as u64
+	};
+}
@@ -0,0 +1,161 @@
+// Compares a run against a saved result file and decides whether any kernel regressed.
+
+use crate::output::ResultRecord;
+use crate::statistics::{mann_whitney_u, minimum_p_value};
+
+pub(crate) const significanceLevel: f64 = 0.05;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Verdict
+{
+	// Slower by more than the threshold, and either significant or not testable.
+	Regression,
+	// Faster by more than the threshold, and either significant or not testable.
+	Improvement,
+	WithinThreshold,
+	NotSignificant
+}
+
+pub(crate) struct Comparison
+{
+	pub(crate) implementation: String,
+	pub(crate) kernel: String,
+	pub(crate) baselineMedian: f64,
+	pub(crate) currentMedian: f64,
+	// The unit of both medians, e.g. "ns".
+	pub(crate) unit: String,
+	// None when there are too few samples for the test to ever reach significance.
+	pub(crate) pValue: Option<f64>,
+	pub(crate) verdict: Verdict
+}
+
+impl Comparison
+{
+	// Above 1 means the current run is faster.
+	pub(crate) fn speedup(&self) -> f64
+	{
+		return self.baselineMedian / self.currentMedian;
+	}
+
+	pub(crate) fn change_percent(&self) -> f64
+	{
+		return (self.currentMedian / self.baselineMedian - 1.0) * 100.0;
+	}
+}
+
+pub(crate) fn compare_records(baseline: &ResultRecord, current: &ResultRecord, thresholdPercent: f64) -> Comparison
+{
+	let testable: bool = minimum_p_value(baseline.samples.len(), current.samples.len()) <= significanceLevel;
+	let pValue: Option<f64> = if (testable) { mann_whitney_u(&baseline.samples, &current.samples) } else { None };
+
+	let mut comparison: Comparison = Comparison
+	{
+		implementation: current.implementation.clone(),
+		kernel: current.kernel.clone(),
+		baselineMedian: baseline.median(),
+		currentMedian: current.median(),
+		unit: current.unit.clone(),
+		pValue,
+		verdict: Verdict::WithinThreshold
+	};
+
+	let change: f64 = comparison.change_percent();
+
+	comparison.verdict = if (pValue.is_some_and(|p| p >= significanceLevel))
+	{
+		Verdict::NotSignificant
+	}
+	else if (change > thresholdPercent)
+	{
+		Verdict::Regression
+	}
+	else if (change < -thresholdPercent)
+	{
+		Verdict::Improvement
+	}
+	else
+	{
+		Verdict::WithinThreshold
+	};
+
+	return comparison;
+}
+
+// Pairs every current record with the baseline record for the same workload. Kernels that can't be
+// paired are reported as notes rather than failing the comparison.
+pub(crate) fn compare_to_baseline(baseline: &[ResultRecord], current: &[ResultRecord], thresholdPercent: f64) -> (Vec<Comparison>, Vec<String>)
+{
+	let mut comparisons: Vec<Comparison> = Vec::new();
+	let mut notes: Vec<String> = Vec::new();
+
+	for record in current
+	{
+		match baseline.iter().find(|candidate| candidate.same_workload(record))
+		{
+			Some(previous) if previous.unit != record.unit =>
+			{
+				notes.push(format!("({}) {}: skipped, the baseline is in {} rather than {}", record.implementation, record.kernel, previous.unit, record.unit));
+			}
+			Some(previous) =>
+			{
+				if (previous.checksumType != record.checksumType || previous.checksum != record.checksum)
+				{
+					notes.push(format!(
+						"({}) {}: result changed from {} to {}",
+						record.implementation,
+						record.kernel,
+						previous.checksum,
+						record.checksum
+					));
+				}
+
+				comparisons.push(compare_records(previous, record, thresholdPercent));
+			}
+			None if baseline.iter().any(|candidate| candidate.implementation == record.implementation && candidate.symbol == record.symbol) =>
+			{
+				notes.push(format!("({}) {}: skipped, the baseline used different parameters", record.implementation, record.kernel));
+			}
+			None => notes.push(format!("({}) {}: not in the baseline", record.implementation, record.kernel))
+		}
+	}
+
+	return (comparisons, notes);
+}
+
+pub(crate) fn format_comparison(comparison: &Comparison) -> String
+{
+	let speedup: f64 = comparison.speedup();
+	let ratio: String = if (speedup >= 1.0)
+	{
+		format!("{:.3}x faster", speedup)
+	}
+	else
+	{
+		format!("{:.3}x slower", 1.0 / speedup)
+	};
+	let pValue: String = match comparison.pValue
+	{
+		Some(p) => format!("p = {:.4}", p),
+		None => String::from("p = n/a")
+	};
+	let verdict: &str = match comparison.verdict
+	{
+		Verdict::Regression => "REGRESSION",
+		Verdict::Improvement => "improvement",
+		Verdict::WithinThreshold => "within threshold",
+		Verdict::NotSignificant => "not significant"
+	};
+
+	return format!(
+		"({}) {}: {} ({:+.1}%), {:.0} -> {:.0} {}, {}, {}",
+		comparison.implementation,
+		comparison.kernel,
+		ratio,
+		comparison.change_percent(),
+		comparison.baselineMedian,
+		comparison.currentMedian,
+		comparison.unit,
+		pValue,
+		verdict
+	);
+}
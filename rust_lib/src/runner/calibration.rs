@@ -0,0 +1,64 @@
+// Scales a kernel's work parameter (see Kernel::work_unit) until one run takes about the target
+// duration, so that the same workload file gives comparable run times on fast and slow machines.
+
+use crate::kernels::Kernel;
+use crate::timer::Timer;
+
+// Runs shorter than this fraction of the target are too noisy to extrapolate from, so the parameter is
+// multiplied by ten instead.
+const extrapolationFloor: f64 = 0.01;
+// A run within this fraction of the target is close enough.
+const tolerance: f64 = 0.1;
+const maximumRounds: usize = 40;
+
+fn time_once(kernel: Kernel, parameters: &[u32], timer: &Timer) -> f64
+{
+	let start: u64 = timer.start();
+	kernel.run(parameters);
+
+	return timer.to_nanoseconds(timer.stop().saturating_sub(start));
+}
+
+// Returns the parameters with the work parameter scaled so that one run takes about `target`
+// nanoseconds. Kernels without a work parameter are returned unchanged.
+pub(crate) fn calibrate(kernel: Kernel, parameters: &[u32], target: f64, timer: &Timer) -> Vec<u32>
+{
+	let mut calibrated: Vec<u32> = parameters.to_vec();
+	let Some((index, _)) = kernel.work_unit() else
+	{
+		return calibrated;
+	};
+
+	let minimum: u32 = kernel.parameter_minimums()[index].max(1);
+	calibrated[index] = minimum;
+
+	for _ in 0..maximumRounds
+	{
+		let elapsed: f64 = time_once(kernel, &calibrated, timer);
+		let current: u32 = calibrated[index];
+
+		if ((elapsed - target).abs() <= target * tolerance || (elapsed > target && current == minimum))
+		{
+			break;
+		}
+
+		let next: f64 = if (elapsed < target * extrapolationFloor)
+		{
+			current as f64 * 10.0
+		}
+		else
+		{
+			(current as f64 * target / elapsed).round()
+		};
+		let next: u32 = next.clamp(minimum as f64, u32::MAX as f64) as u32;
+
+		if (next == current)
+		{
+			break;
+		}
+
+		calibrated[index] = next;
+	}
+
+	return calibrated;
+}
@@ -0,0 +1,325 @@
+// SVG bar charts in the style of the published benchmark_results_<date>/img images: one chart per
+// kernel, one bar per implementation, lower is better. Parameter sweeps get a line chart in the same
+// style instead, with one line per implementation.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::kernels::allKernels;
+use crate::output::ResultRecord;
+use crate::statistics::median;
+
+const chartWidth: f64 = 720.0;
+const chartHeight: f64 = 480.0;
+const plotLeft: f64 = 80.0;
+const plotRight: f64 = 710.0;
+const plotTop: f64 = 80.0;
+const plotBottom: f64 = 445.0;
+const gridLines: f64 = 5.0;
+
+// Colours used by Benchmark Graph Template.ods, plus one for Rust, keyed by runtime_name.
+const implementationColours: [(&str, &str); 9] = [
+	("GCC", "#80ff80"),
+	("Clang", "#6666ff"),
+	("Burst", "#ff6666"),
+	("GCC – Unity", "#66cc66"),
+	("Clang – Unity", "#6666cc"),
+	("IL2CPP", "#ffff66"),
+	("Mono JIT", "#ff66ff"),
+	("Ryu JIT", "#ccffff"),
+	("Rust", "#dea584")
+];
+
+const fallbackColours: [&str; 6] = ["#ff9966", "#99ccff", "#cc99ff", "#ffcc66", "#66cccc", "#cccccc"];
+
+pub(crate) struct Bar
+{
+	pub(crate) implementation: String,
+	pub(crate) value: f64
+}
+
+pub(crate) fn escape_xml(text: &str) -> String
+{
+	return text
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;");
+}
+
+// The harnesses and Benchmark Graph Template.ods don't always spell runtimes the same way. This is
+// the template's spelling, which the charts, the spreadsheet export and the report all match on.
+pub(crate) fn runtime_name(implementation: &str) -> String
+{
+	return match implementation
+	{
+		"RyuJIT" => String::from("Ryu JIT"),
+		"GCC - Unity" => String::from("GCC – Unity"),
+		"Clang - Unity" => String::from("Clang – Unity"),
+		_ => String::from(implementation)
+	};
+}
+
+fn colour_for(implementation: &str, index: usize) -> &'static str
+{
+	let runtime: String = runtime_name(implementation);
+
+	return implementationColours
+		.iter()
+		.find(|(name, _)| *name == runtime)
+		.map(|(_, colour)| *colour)
+		.unwrap_or(fallbackColours[index % fallbackColours.len()]);
+}
+
+// Rounds the axis step up to 1, 2, 2.5 or 5 times a power of ten.
+fn nice_step(maximum: f64) -> f64
+{
+	if (maximum <= 0.0)
+	{
+		return 1.0;
+	}
+
+	let rough: f64 = maximum / gridLines;
+	let magnitude: f64 = 10f64.powf(rough.log10().floor());
+
+	for multiple in [1.0, 2.0, 2.5, 5.0, 10.0]
+	{
+		if (multiple * magnitude >= rough)
+		{
+			return multiple * magnitude;
+		}
+	}
+
+	return 10.0 * magnitude;
+}
+
+// The file name of a kernel's chart, e.g. "Pixar_Raytracer" for "Pixar Raytracer".
+pub(crate) fn chart_name(kernel: &str) -> String
+{
+	return kernel.replace(' ', "_");
+}
+
+pub(crate) fn render_chart(kernel: &str, unit: &str, bars: &[Bar]) -> String
+{
+	let maximum: f64 = bars.iter().map(|bar| bar.value).fold(0.0, f64::max);
+	let step: f64 = nice_step(maximum);
+	let top: f64 = f64::max(step, (maximum / step).ceil() * step);
+	let scale = |value: f64| plotBottom - value / top * (plotBottom - plotTop);
+
+	let mut svg: String = String::new();
+	writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"Arial, sans-serif\">", chartWidth, chartHeight, chartWidth, chartHeight).unwrap();
+	writeln!(svg, "\t<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>").unwrap();
+	writeln!(svg, "\t<text x=\"{}\" y=\"32\" font-size=\"16\" text-anchor=\"middle\">{}</text>", chartWidth / 2.0, escape_xml(kernel)).unwrap();
+	writeln!(svg, "\t<text x=\"{}\" y=\"54\" font-size=\"11\" text-anchor=\"middle\">Lower is Better ({})</text>", chartWidth / 2.0, escape_xml(unit)).unwrap();
+
+	let ticks: usize = (top / step).round() as usize;
+	for i in 0..=ticks
+	{
+		let tick: f64 = step * i as f64;
+		let y: f64 = scale(tick);
+		writeln!(svg, "\t<line x1=\"{}\" y1=\"{:.1}\" x2=\"{}\" y2=\"{:.1}\" stroke=\"#b3b3b3\"/>", plotLeft, y, plotRight, y).unwrap();
+		writeln!(svg, "\t<text x=\"{}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"end\">{}</text>", plotLeft - 6.0, y + 4.0, tick).unwrap();
+	}
+
+	let slot: f64 = (plotRight - plotLeft) / bars.len().max(1) as f64;
+	for (index, bar) in bars.iter().enumerate()
+	{
+		let x: f64 = plotLeft + slot * index as f64;
+		let y: f64 = scale(bar.value);
+		let barWidth: f64 = slot * 0.76;
+
+		writeln!(
+			svg,
+			"\t<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+			x + (slot - barWidth) / 2.0,
+			y,
+			barWidth,
+			plotBottom - y,
+			colour_for(&bar.implementation, index)
+		).unwrap();
+		writeln!(svg, "\t<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" text-anchor=\"middle\">{:.0}</text>", x + slot / 2.0, y - 5.0, bar.value).unwrap();
+		writeln!(svg, "\t<text x=\"{:.1}\" y=\"{}\" font-size=\"12\" text-anchor=\"middle\">{}</text>", x + slot / 2.0, plotBottom + 20.0, escape_xml(&bar.implementation)).unwrap();
+	}
+
+	writeln!(svg, "\t<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#b3b3b3\"/>", plotLeft, plotTop, plotLeft, plotBottom).unwrap();
+	svg.push_str("</svg>\n");
+
+	return svg;
+}
+
+// One line per implementation through the points of a sweep, which are spaced evenly whatever their
+// values. NaN values, e.g. from a point that failed, leave a gap.
+pub(crate) fn render_line_chart(title: &str, unit: &str, labels: &[String], series: &[(String, Vec<f64>)]) -> String
+{
+	let maximum: f64 = series.iter().flat_map(|(_, values)| values.iter()).filter(|value| value.is_finite()).fold(0.0, |maximum, value| f64::max(maximum, *value));
+	let step: f64 = nice_step(maximum);
+	let top: f64 = f64::max(step, (maximum / step).ceil() * step);
+	let scale = |value: f64| plotBottom - value / top * (plotBottom - plotTop);
+	let slot: f64 = (plotRight - plotLeft) / labels.len().max(1) as f64;
+	let x = |index: usize| plotLeft + slot * (index as f64 + 0.5);
+
+	let mut svg: String = String::new();
+	writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"Arial, sans-serif\">", chartWidth, chartHeight, chartWidth, chartHeight).unwrap();
+	writeln!(svg, "\t<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>").unwrap();
+	writeln!(svg, "\t<text x=\"{}\" y=\"32\" font-size=\"16\" text-anchor=\"middle\">{}</text>", chartWidth / 2.0, escape_xml(title)).unwrap();
+	writeln!(svg, "\t<text x=\"{}\" y=\"54\" font-size=\"11\" text-anchor=\"middle\">Lower is Better ({})</text>", chartWidth / 2.0, escape_xml(unit)).unwrap();
+
+	let ticks: usize = (top / step).round() as usize;
+	for i in 0..=ticks
+	{
+		let tick: f64 = step * i as f64;
+		let y: f64 = scale(tick);
+		writeln!(svg, "\t<line x1=\"{}\" y1=\"{:.1}\" x2=\"{}\" y2=\"{:.1}\" stroke=\"#b3b3b3\"/>", plotLeft, y, plotRight, y).unwrap();
+		writeln!(svg, "\t<text x=\"{}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"end\">{}</text>", plotLeft - 6.0, y + 4.0, tick).unwrap();
+	}
+
+	for (index, label) in labels.iter().enumerate()
+	{
+		writeln!(svg, "\t<text x=\"{:.1}\" y=\"{}\" font-size=\"11\" text-anchor=\"middle\">{}</text>", x(index), plotBottom + 20.0, escape_xml(label)).unwrap();
+	}
+
+	for (seriesIndex, (implementation, values)) in series.iter().enumerate()
+	{
+		let colour: &str = colour_for(implementation, seriesIndex);
+		let mut path: String = String::new();
+		let mut drawing: bool = false;
+
+		for (index, value) in values.iter().enumerate()
+		{
+			if (!value.is_finite())
+			{
+				drawing = false;
+				continue;
+			}
+
+			write!(path, "{}{:.1},{:.1} ", if (drawing) { "L" } else { "M" }, x(index), scale(*value)).unwrap();
+			writeln!(svg, "\t<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"/>", x(index), scale(*value), colour).unwrap();
+			drawing = true;
+		}
+
+		writeln!(svg, "\t<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>", path.trim_end(), colour).unwrap();
+
+		let legendY: f64 = plotTop + 16.0 * seriesIndex as f64;
+		writeln!(svg, "\t<rect x=\"{}\" y=\"{:.1}\" width=\"12\" height=\"12\" fill=\"{}\"/>", plotRight - 110.0, legendY - 10.0, colour).unwrap();
+		writeln!(svg, "\t<text x=\"{}\" y=\"{:.1}\" font-size=\"12\">{}</text>", plotRight - 92.0, legendY, escape_xml(implementation)).unwrap();
+	}
+
+	writeln!(svg, "\t<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#b3b3b3\"/>", plotLeft, plotTop, plotLeft, plotBottom).unwrap();
+	svg.push_str("</svg>\n");
+
+	return svg;
+}
+
+// Kernels in Benchmarks.cs order, followed by any the runner doesn't know about.
+pub(crate) fn kernel_order(records: &[ResultRecord]) -> Vec<String>
+{
+	let mut kernels: Vec<String> = allKernels
+		.iter()
+		.map(|kernel| String::from(kernel.name()))
+		.filter(|name| records.iter().any(|record| &record.kernel == name))
+		.collect();
+
+	for record in records
+	{
+		if (!kernels.contains(&record.kernel))
+		{
+			kernels.push(record.kernel.clone());
+		}
+	}
+
+	return kernels;
+}
+
+// Implementations in the order they first appear in the inputs.
+pub(crate) fn implementation_order(records: &[ResultRecord]) -> Vec<String>
+{
+	let mut implementations: Vec<String> = Vec::new();
+
+	for record in records
+	{
+		if (!implementations.contains(&record.implementation))
+		{
+			implementations.push(record.implementation.clone());
+		}
+	}
+
+	return implementations;
+}
+
+fn format_parameters(parameters: &[(String, u32)]) -> String
+{
+	return parameters.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<String>>().join(", ");
+}
+
+// One bar per implementation with the median of all its samples for the kernel, so that several
+// files from the same workload are pooled. A chart can only show one workload in one unit, so mixed
+// parameters, e.g. from a sweep, and mixed units are errors. Results.txt records have no parameters
+// and fit any workload.
+fn kernel_bars(kernel: &str, kernelRecords: &[&ResultRecord], implementations: &[String]) -> Result<(String, Vec<Bar>), String>
+{
+	let mut units: Vec<&str> = kernelRecords.iter().map(|record| record.unit.as_str()).collect();
+	units.sort();
+	units.dedup();
+	if (units.len() > 1)
+	{
+		return Err(format!("{} has results in more than one unit ({}), which can't share a chart", kernel, units.join(", ")));
+	}
+
+	let mut workloads: Vec<&[(String, u32)]> = Vec::new();
+	for record in kernelRecords.iter().filter(|record| !record.parameters.is_empty())
+	{
+		if (!workloads.contains(&record.parameters.as_slice()))
+		{
+			workloads.push(&record.parameters);
+		}
+	}
+	if (workloads.len() > 1)
+	{
+		let listed: Vec<String> = workloads.iter().map(|parameters| format!("({})", format_parameters(parameters))).collect();
+		return Err(format!("{} has results for more than one set of parameters: {}. Chart one workload at a time", kernel, listed.join(", ")));
+	}
+
+	let bars: Vec<Bar> = implementations
+		.iter()
+		.filter_map(|implementation| {
+			let mut samples: Vec<f64> = kernelRecords
+				.iter()
+				.filter(|record| &record.implementation == implementation)
+				.flat_map(|record| record.samples.iter().copied())
+				.collect();
+			if (samples.is_empty())
+			{
+				return None;
+			}
+
+			samples.sort_by(f64::total_cmp);
+			Some(Bar { implementation: implementation.clone(), value: median(&samples) })
+		})
+		.collect();
+
+	return Ok((String::from(units.first().copied().unwrap_or("ticks")), bars));
+}
+
+// Writes one "<Kernel_Name>.svg" per kernel and returns the paths written. Each bar is the median of
+// the implementation's samples.
+pub(crate) fn write_charts(records: &[ResultRecord], outputDirectory: &Path) -> io::Result<Vec<PathBuf>>
+{
+	fs::create_dir_all(outputDirectory)?;
+
+	let implementations: Vec<String> = implementation_order(records);
+	let mut written: Vec<PathBuf> = Vec::new();
+
+	for kernel in kernel_order(records)
+	{
+		let kernelRecords: Vec<&ResultRecord> = records.iter().filter(|record| record.kernel == kernel).collect();
+		let (unit, bars) = kernel_bars(&kernel, &kernelRecords, &implementations).map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?;
+
+		let path: PathBuf = outputDirectory.join(format!("{}.svg", chart_name(&kernel)));
+		fs::write(&path, render_chart(&kernel, &unit, &bars))?;
+		written.push(path);
+	}
+
+	return Ok(written);
+}
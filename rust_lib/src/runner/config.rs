@@ -0,0 +1,187 @@
+// Workload configuration. A TOML file with one table per kernel, named after its exported symbol
+// without the "benchmark_" prefix:
+//
+//     [mandelbrot]
+//     enabled = true
+//     width = 1920
+//     height = 1080
+//     iterations = 8
+//
+// Every key is optional and falls back to the Benchmarks.cs workload. A "history = false" line before
+// the first table keeps runs of the workload out of the history, e.g. for smoke tests. Only the subset
+// of TOML needed for this is understood: tables, comments, integers and booleans.
+
+use std::fs;
+
+use crate::kernels::{allKernels, Kernel};
+
+pub(crate) struct KernelConfig
+{
+	pub(crate) kernel: Kernel,
+	pub(crate) enabled: bool,
+	pub(crate) parameters: Vec<u32>
+}
+
+pub(crate) struct Config
+{
+	// In allKernels order.
+	pub(crate) kernels: Vec<KernelConfig>,
+	// Whether runs are recorded in the history unless --history or --no-history says otherwise.
+	pub(crate) history: bool
+}
+
+impl Default for Config
+{
+	fn default() -> Self
+	{
+		Config
+		{
+			kernels: allKernels
+				.iter()
+				.map(|kernel| KernelConfig
+				{
+					kernel: *kernel,
+					enabled: true,
+					parameters: kernel.default_parameters().to_vec()
+				})
+				.collect(),
+			history: true
+		}
+	}
+}
+
+enum TomlValue
+{
+	Integer(i64),
+	Bool(bool)
+}
+
+fn strip_comment(line: &str) -> &str
+{
+	return line.split_once('#').map(|(code, _)| code).unwrap_or(line);
+}
+
+fn parse_value(text: &str) -> Result<TomlValue, String>
+{
+	if (text == "true")
+	{
+		return Ok(TomlValue::Bool(true));
+	}
+
+	if (text == "false")
+	{
+		return Ok(TomlValue::Bool(false));
+	}
+
+	// TOML allows underscores between digits, e.g. 100_000_000.
+	let digits: String = text.replace('_', "");
+	return digits
+		.parse::<i64>()
+		.map(TomlValue::Integer)
+		.map_err(|_| format!("unsupported value '{}'", text));
+}
+
+fn apply_value(config: &mut KernelConfig, key: &str, value: TomlValue) -> Result<(), String>
+{
+	let kernel: Kernel = config.kernel;
+
+	if (key == "enabled")
+	{
+		return match value
+		{
+			TomlValue::Bool(enabled) =>
+			{
+				config.enabled = enabled;
+				Ok(())
+			}
+			_ => Err(String::from("'enabled' must be true or false"))
+		};
+	}
+
+	let Some(index) = kernel.parameter_names().iter().position(|name| *name == key) else
+	{
+		return Err(format!(
+			"unknown key '{}' for {}, expected enabled or {}",
+			key,
+			kernel.config_name(),
+			kernel.parameter_names().join(", ")
+		));
+	};
+
+	let TomlValue::Integer(number) = value else
+	{
+		return Err(format!("'{}' must be an integer", key));
+	};
+
+	let minimum: u32 = kernel.parameter_minimums()[index];
+	if (number < minimum as i64 || number > u32::MAX as i64)
+	{
+		return Err(format!("'{}' must be between {} and {}", key, minimum, u32::MAX));
+	}
+
+	config.parameters[index] = number as u32;
+	return Ok(());
+}
+
+pub(crate) fn parse_config(text: &str) -> Result<Config, String>
+{
+	let mut config: Config = Config::default();
+	let mut current: Option<usize> = None;
+
+	for (lineIndex, rawLine) in text.lines().enumerate()
+	{
+		let line: &str = strip_comment(rawLine).trim();
+		let lineNumber: usize = lineIndex + 1;
+
+		if (line.is_empty())
+		{
+			continue;
+		}
+
+		if let Some(table) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']'))
+		{
+			let table: &str = table.trim();
+			let index: usize = config.kernels
+				.iter()
+				.position(|entry| entry.kernel.config_name() == table)
+				.ok_or_else(|| format!("line {}: unknown kernel '{}'", lineNumber, table))?;
+
+			current = Some(index);
+			continue;
+		}
+
+		let Some((key, value)) = line.split_once('=') else
+		{
+			return Err(format!("line {}: expected 'key = value' or '[kernel]'", lineNumber));
+		};
+
+		let value: TomlValue = parse_value(value.trim()).map_err(|message| format!("line {}: {}", lineNumber, message))?;
+
+		let Some(index) = current else
+		{
+			if (key.trim() != "history")
+			{
+				return Err(format!("line {}: '{}' must be inside a [kernel] table", lineNumber, key.trim()));
+			}
+
+			let TomlValue::Bool(history) = value else
+			{
+				return Err(format!("line {}: 'history' must be true or false", lineNumber));
+			};
+
+			config.history = history;
+			continue;
+		};
+
+		apply_value(&mut config.kernels[index], key.trim(), value).map_err(|message| format!("line {}: {}", lineNumber, message))?;
+	}
+
+	return Ok(config);
+}
+
+pub(crate) fn load_config(path: &str) -> Result<Config, String>
+{
+	let text: String = fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+
+	return parse_config(&text).map_err(|message| format!("{}: {}", path, message));
+}
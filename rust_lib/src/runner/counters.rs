@@ -0,0 +1,263 @@
+// Performance counters around each timed run, via Linux perf_event_open. Hardware counters explain
+// why one build is slower than another; VMs and containers often don't expose them, in which case
+// only the software counters (task clock, page faults, context switches) are reported.
+
+use std::fs::File;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Counter
+{
+	Cycles,
+	Instructions,
+	BranchMisses,
+	L1dReadMisses,
+	LlcReadMisses,
+	TaskClock,
+	PageFaults,
+	ContextSwitches
+}
+
+const allCounters: [Counter; 8] = [
+	Counter::Cycles,
+	Counter::Instructions,
+	Counter::BranchMisses,
+	Counter::L1dReadMisses,
+	Counter::LlcReadMisses,
+	Counter::TaskClock,
+	Counter::PageFaults,
+	Counter::ContextSwitches
+];
+
+impl Counter
+{
+	// The key used in result files.
+	pub(crate) fn name(self) -> &'static str
+	{
+		return match self
+		{
+			Counter::Cycles => "cycles",
+			Counter::Instructions => "instructions",
+			Counter::BranchMisses => "branch_misses",
+			Counter::L1dReadMisses => "l1d_read_misses",
+			Counter::LlcReadMisses => "llc_read_misses",
+			Counter::TaskClock => "task_clock_ns",
+			Counter::PageFaults => "page_faults",
+			Counter::ContextSwitches => "context_switches"
+		};
+	}
+
+	pub(crate) fn from_name(name: &str) -> Option<Counter>
+	{
+		return allCounters.into_iter().find(|counter| counter.name() == name);
+	}
+
+	pub(crate) fn is_hardware(self) -> bool
+	{
+		return !matches!(self, Counter::TaskClock | Counter::PageFaults | Counter::ContextSwitches);
+	}
+
+	// (type, config) for perf_event_attr, from <linux/perf_event.h>.
+	#[cfg(target_os = "linux")]
+	fn event(self) -> (u32, u64)
+	{
+		const hardware: u32 = 0;
+		const software: u32 = 1;
+		const hardwareCache: u32 = 3;
+		// Cache events are cache | operation << 8 | result << 16, here reads that missed.
+		const readMiss: u64 = 1 << 16;
+
+		return match self
+		{
+			Counter::Cycles => (hardware, 0),
+			Counter::Instructions => (hardware, 1),
+			Counter::BranchMisses => (hardware, 5),
+			Counter::L1dReadMisses => (hardwareCache, readMiss),
+			Counter::LlcReadMisses => (hardwareCache, 2 | readMiss),
+			Counter::TaskClock => (software, 1),
+			Counter::PageFaults => (software, 2),
+			Counter::ContextSwitches => (software, 3)
+		};
+	}
+}
+
+#[cfg(target_os = "linux")]
+mod ffi
+{
+	// PERF_ATTR_SIZE_VER1. Later fields are optional and the kernel treats them as zero.
+	#[repr(C)]
+	#[derive(Default)]
+	pub(super) struct PerfEventAttr
+	{
+		pub(super) kind: u32,
+		pub(super) size: u32,
+		pub(super) config: u64,
+		pub(super) samplePeriod: u64,
+		pub(super) sampleType: u64,
+		pub(super) readFormat: u64,
+		pub(super) flags: u64,
+		pub(super) wakeupEvents: u32,
+		pub(super) bpType: u32,
+		pub(super) config1: u64,
+		pub(super) config2: u64
+	}
+
+	pub(super) const flagDisabled: u64 = 1 << 0;
+	pub(super) const flagExcludeKernel: u64 = 1 << 5;
+	pub(super) const flagExcludeHypervisor: u64 = 1 << 6;
+
+	pub(super) const formatTotalTimeEnabled: u64 = 1 << 0;
+	pub(super) const formatTotalTimeRunning: u64 = 1 << 1;
+
+	pub(super) const PERF_FLAG_FD_CLOEXEC: u64 = 1 << 3;
+	pub(super) const PERF_EVENT_IOC_ENABLE: u64 = 0x2400;
+	pub(super) const PERF_EVENT_IOC_DISABLE: u64 = 0x2401;
+	pub(super) const PERF_EVENT_IOC_RESET: u64 = 0x2403;
+
+	#[cfg(target_arch = "x86_64")]
+	pub(super) const SYS_perf_event_open: i64 = 298;
+	#[cfg(target_arch = "aarch64")]
+	pub(super) const SYS_perf_event_open: i64 = 241;
+
+	extern "C"
+	{
+		pub(super) fn syscall(number: i64, ...) -> i64;
+		pub(super) fn ioctl(fd: i32, request: u64, ...) -> i32;
+	}
+}
+
+// The counters that could be opened for the calling thread. Each counter is its own event rather than
+// one group, so that a PMU with too few registers multiplexes them instead of refusing the group;
+// readings are scaled by the fraction of the time each event was actually counting.
+pub(crate) struct CounterSet
+{
+	open: Vec<(Counter, File)>,
+	// Counters that could not be opened, with the reason.
+	pub(crate) unavailable: Vec<(Counter, String)>
+}
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn open_counter(counter: Counter) -> Result<File, String>
+{
+	use std::os::unix::io::FromRawFd;
+
+	let (kind, config) = counter.event();
+	let attributes: ffi::PerfEventAttr = ffi::PerfEventAttr
+	{
+		kind,
+		size: std::mem::size_of::<ffi::PerfEventAttr>() as u32,
+		config,
+		readFormat: ffi::formatTotalTimeEnabled | ffi::formatTotalTimeRunning,
+		// Only user space is counted, which is all that perf_event_paranoid=2 allows unprivileged.
+		flags: ffi::flagDisabled | ffi::flagExcludeKernel | ffi::flagExcludeHypervisor,
+		..Default::default()
+	};
+
+	// This thread, on any CPU, in no group.
+	let fd: i64 = unsafe { ffi::syscall(ffi::SYS_perf_event_open, &attributes as *const ffi::PerfEventAttr, 0i32, -1i32, -1i32, ffi::PERF_FLAG_FD_CLOEXEC) };
+	if (fd < 0)
+	{
+		return Err(std::io::Error::last_os_error().to_string());
+	}
+
+	return Ok(unsafe { File::from_raw_fd(fd as i32) });
+}
+
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+fn open_counter(_counter: Counter) -> Result<File, String>
+{
+	return Err(String::from("perf_event_open is not supported on this platform"));
+}
+
+#[cfg(target_os = "linux")]
+fn control(file: &File, request: u64)
+{
+	use std::os::unix::io::AsRawFd;
+
+	unsafe { ffi::ioctl(file.as_raw_fd(), request, 0) };
+}
+
+// Reads (value, time enabled, time running) and scales the value up for the time the counter was
+// multiplexed out. NaN if it never ran.
+#[cfg(target_os = "linux")]
+fn read_scaled(mut file: &File) -> f64
+{
+	use std::io::Read;
+
+	let mut buffer: [u8; 24] = [0; 24];
+	if (file.read_exact(&mut buffer).is_err())
+	{
+		return f64::NAN;
+	}
+
+	let word = |index: usize| u64::from_ne_bytes(buffer[index * 8..index * 8 + 8].try_into().unwrap()) as f64;
+	let (value, enabled, running) = (word(0), word(1), word(2));
+
+	if (running == 0.0)
+	{
+		return f64::NAN;
+	}
+
+	return value * enabled / running;
+}
+
+impl CounterSet
+{
+	pub(crate) fn open() -> CounterSet
+	{
+		let mut set: CounterSet = CounterSet { open: Vec::new(), unavailable: Vec::new() };
+
+		for counter in allCounters
+		{
+			match open_counter(counter)
+			{
+				Ok(file) => set.open.push((counter, file)),
+				Err(reason) => set.unavailable.push((counter, reason))
+			}
+		}
+
+		return set;
+	}
+
+	pub(crate) fn counters(&self) -> Vec<Counter>
+	{
+		return self.open.iter().map(|(counter, _)| *counter).collect();
+	}
+
+	pub(crate) fn has_hardware(&self) -> bool
+	{
+		return self.open.iter().any(|(counter, _)| counter.is_hardware());
+	}
+
+	#[cfg(target_os = "linux")]
+	pub(crate) fn start(&self)
+	{
+		for (_, file) in &self.open
+		{
+			control(file, ffi::PERF_EVENT_IOC_RESET);
+			control(file, ffi::PERF_EVENT_IOC_ENABLE);
+		}
+	}
+
+	// Stops counting and returns one reading per open counter, in the order of counters().
+	#[cfg(target_os = "linux")]
+	pub(crate) fn stop(&self) -> Vec<f64>
+	{
+		for (_, file) in &self.open
+		{
+			control(file, ffi::PERF_EVENT_IOC_DISABLE);
+		}
+
+		return self.open.iter().map(|(_, file)| read_scaled(file)).collect();
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	pub(crate) fn start(&self)
+	{
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	pub(crate) fn stop(&self) -> Vec<f64>
+	{
+		return Vec::new();
+	}
+}
@@ -0,0 +1,122 @@
+// "runner diff", which lines up the kernels of two result files, e.g. rustc stable against beta or
+// -C target-cpu=native against the default, and says which changes the samples actually support.
+// The verdicts are the ones --baseline uses, so a change whose Mann-Whitney p-value is above the
+// significance level is never reported as faster or slower.
+
+use crate::baseline::{significanceLevel, Comparison, Verdict};
+use crate::json::JsonValue;
+
+// The environment fields that usually explain a difference between two runs.
+const comparedFields: [&str; 11] =
+	["rustc_version", "profile", "opt_level", "target", "rustflags", "target_features", "cpu_model", "kernel_version", "governor", "timer", "order"];
+
+fn environment_value(environment: &JsonValue, key: &str) -> String
+{
+	return match environment.get(key)
+	{
+		Some(JsonValue::String(value)) => value.clone(),
+		Some(JsonValue::Array(items)) => items.iter().filter_map(JsonValue::as_str).collect::<Vec<&str>>().join(","),
+		Some(JsonValue::Number(value)) => value.to_string(),
+		Some(JsonValue::Bool(value)) => value.to_string(),
+		_ => String::from("unknown")
+	};
+}
+
+// "    rustflags: '' -> '-C target-cpu=native'" for every compared field that differs.
+pub(crate) fn environment_differences(old: &JsonValue, new: &JsonValue) -> Vec<String>
+{
+	return comparedFields
+		.iter()
+		.filter_map(|key| {
+			let (before, after) = (environment_value(old, key), environment_value(new, key));
+			(before != after).then(|| format!("    {}: '{}' -> '{}'", key, before, after))
+		})
+		.collect();
+}
+
+// With too few samples the test can't reach significance at all, so the change is only shown.
+fn verdict_label(comparison: &Comparison) -> &'static str
+{
+	if (comparison.pValue.is_none())
+	{
+		return "untested, too few samples";
+	}
+
+	return match comparison.verdict
+	{
+		Verdict::Improvement => "faster",
+		Verdict::Regression => "slower",
+		Verdict::WithinThreshold => "within threshold",
+		Verdict::NotSignificant => "not significant"
+	};
+}
+
+// Pads every column to its widest cell. The first `textColumns` columns are aligned left and the
+// numbers after them right. The last column is left as it is.
+pub(crate) fn format_table(rows: &[Vec<String>], textColumns: usize) -> Vec<String>
+{
+	let columns: usize = rows.iter().map(Vec::len).max().unwrap_or(0);
+	let widths: Vec<usize> = (0..columns)
+		.map(|column| rows.iter().filter_map(|row| row.get(column)).map(|cell| cell.chars().count()).max().unwrap_or(0))
+		.collect();
+
+	return rows
+		.iter()
+		.map(|row| {
+			let cells: Vec<String> = row
+				.iter()
+				.enumerate()
+				.map(|(column, cell)| match column
+				{
+					_ if (column < textColumns) => format!("{:<width$}", cell, width = widths[column]),
+					_ if (column + 1 == row.len()) => cell.clone(),
+					_ => format!("{:>width$}", cell, width = widths[column])
+				})
+				.collect();
+			String::from(cells.join("  ").trim_end())
+		})
+		.collect();
+}
+
+// One row per kernel: both medians, the absolute and relative change, the p-value and the verdict.
+pub(crate) fn format_diff(comparisons: &[Comparison]) -> Vec<String>
+{
+	let mut rows: Vec<Vec<String>> = vec![
+		["Implementation", "Kernel", "Old", "New", "Change", "", "p", "Verdict"].iter().map(|cell| String::from(*cell)).collect()
+	];
+
+	for comparison in comparisons
+	{
+		rows.push(vec![
+			comparison.implementation.clone(),
+			comparison.kernel.clone(),
+			format!("{:.0} {}", comparison.baselineMedian, comparison.unit),
+			format!("{:.0} {}", comparison.currentMedian, comparison.unit),
+			format!("{:+.0} {}", comparison.currentMedian - comparison.baselineMedian, comparison.unit),
+			format!("{:+.1}%", comparison.change_percent()),
+			comparison.pValue.map(|p| format!("{:.4}", p)).unwrap_or_else(|| String::from("n/a")),
+			String::from(verdict_label(comparison))
+		]);
+	}
+
+	return format_table(&rows, 2);
+}
+
+// e.g. "2 faster, 1 slower, 3 not significant (p >= 0.05)".
+pub(crate) fn summarise_diff(comparisons: &[Comparison]) -> String
+{
+	let labels: [&str; 5] = ["faster", "slower", "within threshold", "not significant", "untested, too few samples"];
+	let parts: Vec<String> = labels
+		.iter()
+		.map(|label| (label, comparisons.iter().filter(|comparison| verdict_label(comparison) == *label).count()))
+		.filter(|(_, count)| *count > 0)
+		.map(|(label, count)| match *label
+		{
+			"not significant" => format!("{} not significant (p >= {})", count, significanceLevel),
+			"untested, too few samples" => format!("{} untested", count),
+			_ => format!("{} {}", count, label)
+		})
+		.collect();
+
+	return parts.join(", ");
+}
@@ -0,0 +1,208 @@
+// Host and build facts recorded with every run, replacing the hand-written "Software versions and
+// environment" section of the published results.
+
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::affinity::{current_affinity, current_nice, current_scheduler, format_cpu_list, SchedulerPolicy};
+use crate::implementation::{Implementation, Library};
+use crate::json::{object, string, JsonValue};
+use crate::ordering::Order;
+use crate::timer::Timer;
+
+pub(crate) struct Environment
+{
+	pub(crate) timestamp: u64,
+	pub(crate) rustcVersion: &'static str,
+	pub(crate) profile: &'static str,
+	pub(crate) optLevel: &'static str,
+	pub(crate) target: &'static str,
+	pub(crate) rustflags: &'static str,
+	pub(crate) targetFeatures: Vec<&'static str>,
+	pub(crate) cpuModel: String,
+	pub(crate) logicalCpus: usize,
+	pub(crate) kernelVersion: String,
+	pub(crate) governor: String,
+	pub(crate) smtControl: String,
+	pub(crate) smtActive: bool,
+	// How the runner itself was scheduled, after --cpu, --nice and --scheduler.
+	pub(crate) affinity: Vec<usize>,
+	pub(crate) nice: Option<i32>,
+	pub(crate) scheduler: Option<SchedulerPolicy>,
+	pub(crate) timer: &'static str,
+	pub(crate) tscFrequency: Option<f64>,
+	pub(crate) order: Order,
+	// Only for the random order.
+	pub(crate) seed: Option<u64>,
+	// The libraries loaded with --library, in the order given.
+	pub(crate) libraries: Vec<LoadedLibrary>
+}
+
+pub(crate) struct LoadedLibrary
+{
+	pub(crate) name: String,
+	pub(crate) path: String,
+	// e.g. "GCC: (Debian 12.2.0-14) 12.2.0", read from the library's .comment section.
+	pub(crate) compiler: Option<String>
+}
+
+// Values written by build.rs.
+const rustcVersion: &str = env!("BENCHMARKS_RUSTC_VERSION");
+const profile: &str = env!("BENCHMARKS_PROFILE");
+const optLevel: &str = env!("BENCHMARKS_OPT_LEVEL");
+const target: &str = env!("BENCHMARKS_TARGET");
+const targetFeatures: &str = env!("BENCHMARKS_TARGET_FEATURES");
+const rustflags: &str = env!("BENCHMARKS_RUSTFLAGS");
+
+const unavailable: &str = "unavailable";
+
+fn read_trimmed(path: &str) -> Option<String>
+{
+	return fs::read_to_string(path).ok().map(|contents| String::from(contents.trim()));
+}
+
+fn read_cpu_model() -> String
+{
+	let cpuinfo: String = fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+
+	for line in cpuinfo.lines()
+	{
+		// x86 reports "model name", some ARM kernels only report "Hardware" or "CPU part".
+		if let Some((key, value)) = line.split_once(':')
+		{
+			let key: &str = key.trim();
+			if (key == "model name" || key == "Hardware")
+			{
+				return String::from(value.trim());
+			}
+		}
+	}
+
+	return String::from(unavailable);
+}
+
+pub(crate) fn current_timestamp() -> u64
+{
+	return SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs())
+		.unwrap_or(0);
+}
+
+pub(crate) fn capture_environment(timer: &Timer, order: Order, seed: Option<u64>, implementations: &[Implementation]) -> Environment
+{
+	return Environment
+	{
+		timestamp: current_timestamp(),
+		rustcVersion,
+		profile,
+		optLevel,
+		target,
+		rustflags,
+		targetFeatures: targetFeatures.split(',').filter(|feature| !feature.is_empty()).collect(),
+		cpuModel: read_cpu_model(),
+		logicalCpus: std::thread::available_parallelism().map(|count| count.get()).unwrap_or(1),
+		kernelVersion: read_trimmed("/proc/sys/kernel/osrelease").unwrap_or_else(|| String::from(unavailable)),
+		governor: read_trimmed("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor").unwrap_or_else(|| String::from(unavailable)),
+		smtControl: read_trimmed("/sys/devices/system/cpu/smt/control").unwrap_or_else(|| String::from(unavailable)),
+		smtActive: read_trimmed("/sys/devices/system/cpu/smt/active").is_some_and(|active| active == "1"),
+		affinity: current_affinity(),
+		nice: current_nice(),
+		scheduler: current_scheduler(),
+		timer: timer.name(),
+		tscFrequency: timer.tsc_frequency(),
+		order,
+		seed,
+		libraries: implementations
+			.iter()
+			.filter_map(|implementation| {
+				let library: &Library = implementation.library()?;
+				Some(LoadedLibrary { name: String::from(implementation.name()), path: library.path.clone(), compiler: library.compiler.clone() })
+			})
+			.collect()
+	};
+}
+
+impl Environment
+{
+	fn affinity_text(&self) -> String
+	{
+		return if (self.affinity.is_empty()) { String::from(unavailable) } else { format_cpu_list(&self.affinity) };
+	}
+
+	pub(crate) fn has_target_feature(&self, feature: &str) -> bool
+	{
+		return self.targetFeatures.contains(&feature);
+	}
+
+	// One line for the console, e.g. "rustc 1.95.0 (release, opt-level 3, avx2 off, fma off) on ..."
+	pub(crate) fn summary(&self) -> String
+	{
+		let onOff = |enabled: bool| if (enabled) { "on" } else { "off" };
+
+		return format!(
+			"{} ({}, opt-level {}, avx2 {}, fma {}) on {}, Linux {}, governor {}, SMT {}, CPUs {}",
+			self.rustcVersion,
+			self.profile,
+			self.optLevel,
+			onOff(self.has_target_feature("avx2")),
+			onOff(self.has_target_feature("fma")),
+			self.cpuModel,
+			self.kernelVersion,
+			self.governor,
+			self.smtControl,
+			self.affinity_text()
+		);
+	}
+}
+
+pub(crate) fn environment_to_json(environment: &Environment) -> JsonValue
+{
+	return object(vec![
+		("timestamp", JsonValue::Number(environment.timestamp as f64)),
+		("rustc_version", string(environment.rustcVersion)),
+		("profile", string(environment.profile)),
+		("opt_level", string(environment.optLevel)),
+		("target", string(environment.target)),
+		("rustflags", string(environment.rustflags)),
+		("target_features", JsonValue::Array(environment.targetFeatures.iter().map(|feature| string(feature)).collect())),
+		("avx2", JsonValue::Bool(environment.has_target_feature("avx2"))),
+		("fma", JsonValue::Bool(environment.has_target_feature("fma"))),
+		("cpu_model", string(&environment.cpuModel)),
+		("logical_cpus", JsonValue::Number(environment.logicalCpus as f64)),
+		("kernel_version", string(&environment.kernelVersion)),
+		("governor", string(&environment.governor)),
+		("smt_control", string(&environment.smtControl)),
+		("smt_active", JsonValue::Bool(environment.smtActive)),
+		("cpu_affinity", string(&environment.affinity_text())),
+		("nice", JsonValue::Number(environment.nice.map(|nice| nice as f64).unwrap_or(f64::NAN))),
+		("scheduler", string(environment.scheduler.map(SchedulerPolicy::as_str).unwrap_or(unavailable))),
+		("timer", string(environment.timer)),
+		("tsc_frequency_hz", JsonValue::Number(environment.tscFrequency.unwrap_or(f64::NAN))),
+		("order", string(environment.order.as_str())),
+		// A string like the checksums, since a u64 doesn't survive a trip through a double.
+		("seed", environment.seed.map(|seed| JsonValue::String(seed.to_string())).unwrap_or_else(|| string(unavailable))),
+		("libraries", JsonValue::Array(environment.libraries.iter().map(|library| object(vec![
+			("name", string(&library.name)),
+			("path", string(&library.path)),
+			("compiler", string(library.compiler.as_deref().unwrap_or(unavailable)))
+		])).collect()))
+	]);
+}
+
+// Converts a Unix timestamp to a (year, month, day) UTC date, using Howard Hinnant's days_from_civil
+// algorithm in reverse.
+pub(crate) fn date_from_timestamp(timestamp: u64) -> (i64, u32, u32)
+{
+	let days: i64 = (timestamp / 86400) as i64 + 719468;
+	let era: i64 = days.div_euclid(146097);
+	let dayOfEra: i64 = days - era * 146097;
+	let yearOfEra: i64 = (dayOfEra - dayOfEra / 1460 + dayOfEra / 36524 - dayOfEra / 146096) / 365;
+	let dayOfYear: i64 = dayOfEra - (365 * yearOfEra + yearOfEra / 4 - yearOfEra / 100);
+	let shiftedMonth: i64 = (5 * dayOfYear + 2) / 153;
+	let day: u32 = (dayOfYear - (153 * shiftedMonth + 2) / 5 + 1) as u32;
+	let month: u32 = if (shiftedMonth < 10) { shiftedMonth + 3 } else { shiftedMonth - 9 } as u32;
+	let year: i64 = yearOfEra + era * 400 + if (month <= 2) { 1 } else { 0 };
+
+	return (year, month, day);
+}
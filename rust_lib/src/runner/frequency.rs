@@ -0,0 +1,215 @@
+// CPU frequency and temperature during each timed run, to catch laptops that throttle halfway
+// through a kernel. A background thread reads the benchmark core's scaling_cur_freq and every thermal
+// zone while a sample runs, and the readings are summarised per sample. The thread sleeps between
+// readings, so it takes very little CPU time away from the kernel, and with --cpu it is moved off the
+// pinned core unless that is the only one.
+
+use std::fs;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::affinity::avoid_cpu;
+
+const cpuRoot: &str = "/sys/devices/system/cpu";
+const thermalRoot: &str = "/sys/class/thermal";
+const readingInterval: Duration = Duration::from_millis(5);
+
+// The frequency of one core while one sample ran.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FrequencySample
+{
+	pub(crate) cpu: usize,
+	// In MHz, or NaN if the frequency couldn't be read during the sample.
+	pub(crate) minimum: f64,
+	pub(crate) maximum: f64,
+	pub(crate) average: f64,
+	// Hottest thermal zone seen during the sample, in degrees Celsius, if there are any zones.
+	pub(crate) temperature: Option<f64>
+}
+
+#[derive(Clone, Copy)]
+struct Reading
+{
+	frequency: Option<f64>,
+	temperature: Option<f64>
+}
+
+enum Request
+{
+	Begin(usize),
+	End,
+	Stop
+}
+
+#[cfg(target_os = "linux")]
+mod ffi
+{
+	extern "C"
+	{
+		pub(super) fn sched_getcpu() -> i32;
+	}
+}
+
+// The CPU the calling thread is running on, which is the pinned one with --cpu.
+#[cfg(target_os = "linux")]
+fn current_cpu() -> usize
+{
+	return unsafe { ffi::sched_getcpu() }.max(0) as usize;
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_cpu() -> usize
+{
+	return 0;
+}
+
+// scaling_cur_freq is in kHz.
+fn read_frequency(cpu: usize) -> Option<f64>
+{
+	let text: String = fs::read_to_string(format!("{}/cpu{}/cpufreq/scaling_cur_freq", cpuRoot, cpu)).ok()?;
+
+	return text.trim().parse::<f64>().ok().map(|kilohertz| kilohertz / 1000.0);
+}
+
+// The hottest thermal zone, read from the millidegree "temp" files.
+fn read_temperature() -> Option<f64>
+{
+	return fs::read_dir(thermalRoot)
+		.ok()?
+		.filter_map(Result::ok)
+		.filter(|entry| entry.file_name().to_string_lossy().starts_with("thermal_zone"))
+		.filter_map(|entry| fs::read_to_string(entry.path().join("temp")).ok())
+		.filter_map(|text| text.trim().parse::<f64>().ok())
+		.map(|millidegrees| millidegrees / 1000.0)
+		.reduce(f64::max);
+}
+
+fn read(cpu: usize) -> Reading
+{
+	return Reading { frequency: read_frequency(cpu), temperature: read_temperature() };
+}
+
+fn summarise(cpu: usize, readings: &[Reading]) -> FrequencySample
+{
+	let frequencies: Vec<f64> = readings.iter().filter_map(|reading| reading.frequency).collect();
+
+	return FrequencySample
+	{
+		cpu,
+		minimum: frequencies.iter().copied().reduce(f64::min).unwrap_or(f64::NAN),
+		maximum: frequencies.iter().copied().reduce(f64::max).unwrap_or(f64::NAN),
+		average: frequencies.iter().sum::<f64>() / frequencies.len() as f64,
+		temperature: readings.iter().filter_map(|reading| reading.temperature).reduce(f64::max)
+	};
+}
+
+// Reads every `readingInterval` from Begin until End, plus once at each end, and sends the readings
+// back.
+fn sampler(requests: mpsc::Receiver<Request>, readings: mpsc::Sender<Vec<Reading>>)
+{
+	while let Ok(Request::Begin(cpu)) = requests.recv()
+	{
+		let mut taken: Vec<Reading> = vec![read(cpu)];
+
+		loop
+		{
+			match requests.recv_timeout(readingInterval)
+			{
+				Err(mpsc::RecvTimeoutError::Timeout) => taken.push(read(cpu)),
+				Ok(Request::End) => break,
+				_ => return
+			}
+		}
+
+		taken.push(read(cpu));
+		if (readings.send(taken).is_err())
+		{
+			return;
+		}
+	}
+}
+
+pub(crate) struct FrequencyMonitor
+{
+	// The pinned CPU, or None to follow the benchmark thread.
+	cpu: Option<usize>,
+	requests: mpsc::Sender<Request>,
+	readings: mpsc::Receiver<Vec<Reading>>,
+	thread: Option<thread::JoinHandle<()>>
+}
+
+impl FrequencyMonitor
+{
+	// Fails if the CPU doesn't report its frequency, e.g. in most virtual machines. The sampler thread
+	// leaves the pinned CPU first, which matters in --isolate workers: they inherit the pinning, so the
+	// thread would otherwise start on the benchmark's core.
+	pub(crate) fn start(cpu: Option<usize>) -> Result<FrequencyMonitor, String>
+	{
+		let probe: usize = cpu.unwrap_or_else(current_cpu);
+		if (read_frequency(probe).is_none())
+		{
+			return Err(format!("CPU {} doesn't report its frequency through {}/cpu{}/cpufreq", probe, cpuRoot, probe));
+		}
+
+		let (requests, requestReceiver) = mpsc::channel::<Request>();
+		let (readingSender, readings) = mpsc::channel::<Vec<Reading>>();
+		let thread = thread::Builder::new()
+			.name(String::from("frequency monitor"))
+			.spawn(move || {
+				// On a single CPU the thread has to share it.
+				if let Some(pinned) = cpu
+				{
+					let _ = avoid_cpu(pinned);
+				}
+				sampler(requestReceiver, readingSender)
+			})
+			.map_err(|error| format!("Could not start the frequency monitor: {}", error))?;
+
+		return Ok(FrequencyMonitor { cpu, requests, readings, thread: Some(thread) });
+	}
+
+	// Starts watching the benchmark's core, just before a timed run.
+	pub(crate) fn begin(&self) -> usize
+	{
+		let cpu: usize = self.cpu.unwrap_or_else(current_cpu);
+		let _ = self.requests.send(Request::Begin(cpu));
+
+		return cpu;
+	}
+
+	// Stops watching, just after the timed run, and summarises what was read.
+	pub(crate) fn end(&self, cpu: usize) -> FrequencySample
+	{
+		let _ = self.requests.send(Request::End);
+		let readings: Vec<Reading> = self.readings.recv().unwrap_or_default();
+
+		return summarise(cpu, &readings);
+	}
+}
+
+impl Drop for FrequencyMonitor
+{
+	fn drop(&mut self)
+	{
+		let _ = self.requests.send(Request::Stop);
+		if let Some(thread) = self.thread.take()
+		{
+			let _ = thread.join();
+		}
+	}
+}
+
+// How far the frequency moved over all samples, as a percentage of the highest reading.
+pub(crate) fn frequency_variation(samples: &[FrequencySample]) -> Option<f64>
+{
+	let lowest: f64 = samples.iter().map(|sample| sample.minimum).filter(|value| !value.is_nan()).reduce(f64::min)?;
+	let highest: f64 = samples.iter().map(|sample| sample.maximum).filter(|value| !value.is_nan()).reduce(f64::max)?;
+
+	if (highest <= 0.0)
+	{
+		return None;
+	}
+
+	return Some((highest - lowest) / highest * 100.0);
+}
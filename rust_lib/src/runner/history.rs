@@ -0,0 +1,205 @@
+// Keeps every run so that results can be followed over time, instead of being buried in Results.txt.
+// Each run is appended to a JSON-lines file as one line holding the same document --json writes,
+// environment included. "runner history" reads it back and shows how a kernel's median moved from
+// run to run, or from one rustc version to the next.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+
+use crate::environment::Environment;
+use crate::isolation::Failure;
+use crate::json::JsonValue;
+use crate::measurement::Measurement;
+use crate::output::{parse_results, results_to_json, ResultRecord};
+use crate::report::format_iso_date;
+use crate::statistics::median;
+
+pub(crate) const defaultHistoryPath: &str = "./benchmark_history.jsonl";
+
+// One line of the history file.
+pub(crate) struct HistoryRun
+{
+	pub(crate) timestamp: u64,
+	pub(crate) rustcVersion: String,
+	pub(crate) records: Vec<ResultRecord>
+}
+
+// One point of a trend: a single run, or consecutive runs with the same rustc when grouped by
+// toolchain.
+pub(crate) struct TrendPoint
+{
+	// The last run of the point.
+	pub(crate) timestamp: u64,
+	pub(crate) rustcVersion: String,
+	pub(crate) runs: usize,
+	pub(crate) samples: Vec<f64>
+}
+
+impl TrendPoint
+{
+	pub(crate) fn median(&self) -> f64
+	{
+		let mut sorted: Vec<f64> = self.samples.clone();
+		sorted.sort_by(f64::total_cmp);
+
+		return median(&sorted);
+	}
+}
+
+// The history of one kernel of one implementation with one set of parameters.
+pub(crate) struct Trend
+{
+	pub(crate) implementation: String,
+	pub(crate) kernel: String,
+	pub(crate) symbol: String,
+	pub(crate) parameters: Vec<(String, u32)>,
+	pub(crate) unit: String,
+	pub(crate) points: Vec<TrendPoint>
+}
+
+pub(crate) fn append_run(path: &str, environment: &Environment, measurements: &[Measurement], failures: &[Failure]) -> io::Result<()>
+{
+	let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+	let mut line: String = results_to_json(environment, measurements, failures).to_compact_string();
+	line.push('\n');
+
+	return file.write_all(line.as_bytes());
+}
+
+// Lines that can't be read, e.g. one cut short when a run was killed while writing, are skipped with
+// a warning rather than hiding the rest of the history.
+pub(crate) fn read_history(path: &str) -> Result<(Vec<HistoryRun>, Vec<String>), String>
+{
+	let text: String = fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+	let mut runs: Vec<HistoryRun> = Vec::new();
+	let mut warnings: Vec<String> = Vec::new();
+
+	for (index, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty())
+	{
+		match parse_results(line)
+		{
+			Ok(file) =>
+			{
+				let environment: Option<&JsonValue> = file.environment.as_ref();
+				runs.push(HistoryRun
+				{
+					timestamp: environment.and_then(|environment| environment.get("timestamp")).and_then(JsonValue::as_f64).unwrap_or(0.0) as u64,
+					rustcVersion: String::from(environment.and_then(|environment| environment.get("rustc_version")).and_then(JsonValue::as_str).unwrap_or("unknown rustc")),
+					records: file.records
+				});
+			}
+			Err(message) => warnings.push(format!("Warning: skipped line {} of {}: {}", index + 1, path, message))
+		}
+	}
+
+	// Runs are appended as they finish, but the clock may have been changed in between.
+	runs.sort_by_key(|run| run.timestamp);
+
+	return Ok((runs, warnings));
+}
+
+// Follows every selected record through the runs. With `byToolchain`, consecutive runs with the same
+// rustc version are pooled into one point.
+pub(crate) fn build_trends(runs: &[HistoryRun], selected: impl Fn(&ResultRecord) -> bool, byToolchain: bool) -> Vec<Trend>
+{
+	let mut trends: Vec<Trend> = Vec::new();
+
+	for run in runs
+	{
+		for record in run.records.iter().filter(|record| selected(record))
+		{
+			let index: usize = match trends.iter().position(|trend| {
+				trend.implementation == record.implementation && trend.symbol == record.symbol && trend.parameters == record.parameters && trend.unit == record.unit
+			})
+			{
+				Some(index) => index,
+				None =>
+				{
+					trends.push(Trend
+					{
+						implementation: record.implementation.clone(),
+						kernel: record.kernel.clone(),
+						symbol: record.symbol.clone(),
+						parameters: record.parameters.clone(),
+						unit: record.unit.clone(),
+						points: Vec::new()
+					});
+					trends.len() - 1
+				}
+			};
+
+			let points: &mut Vec<TrendPoint> = &mut trends[index].points;
+			match points.last_mut()
+			{
+				Some(point) if (byToolchain && point.rustcVersion == run.rustcVersion) =>
+				{
+					point.timestamp = run.timestamp;
+					point.runs += 1;
+					point.samples.extend_from_slice(&record.samples);
+				}
+				_ => points.push(TrendPoint
+				{
+					timestamp: run.timestamp,
+					rustcVersion: run.rustcVersion.clone(),
+					runs: 1,
+					samples: record.samples.clone()
+				})
+			}
+		}
+	}
+
+	return trends;
+}
+
+// e.g. "2022-10-20 14:03", in UTC.
+fn format_date_time(timestamp: u64) -> String
+{
+	let secondOfDay: u64 = timestamp % 86400;
+
+	return format!("{} {:02}:{:02}", format_iso_date(timestamp), secondOfDay / 3600, secondOfDay / 60 % 60);
+}
+
+fn plural(count: usize, noun: &str) -> String
+{
+	return format!("{} {}{}", count, noun, if (count == 1) { "" } else { "s" });
+}
+
+// A header naming the kernel, then one line per point with the change from the point before, e.g.
+// "    2022-10-20 14:03  rustc 1.95.0 (...)  1 run, 5 samples  median 123456 ns  +2.1%". Only the
+// last `last` points are shown.
+pub(crate) fn format_trend(trend: &Trend, last: usize) -> String
+{
+	let parameters: Vec<String> = trend.parameters.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+	let mut lines: Vec<String> = vec![format!("({}) {} ({}):", trend.implementation, trend.kernel, parameters.join(", "))];
+	let points: &[TrendPoint] = &trend.points[trend.points.len().saturating_sub(last)..];
+	let versionWidth: usize = points.iter().map(|point| point.rustcVersion.len()).max().unwrap_or(0);
+	let mut previous: Option<f64> = None;
+
+	for point in points
+	{
+		let median: f64 = point.median();
+		let change: String = previous.map(|previous| format!("  {:+.1}%", (median / previous - 1.0) * 100.0)).unwrap_or_default();
+
+		lines.push(format!(
+			"    {}  {:versionWidth$}  {}, {}  median {:.0} {}{}",
+			format_date_time(point.timestamp),
+			point.rustcVersion,
+			plural(point.runs, "run"),
+			plural(point.samples.len(), "sample"),
+			median,
+			trend.unit,
+			change
+		));
+		previous = Some(median);
+	}
+
+	if let (Some(first), Some(lastPoint)) = (points.first(), points.last())
+	{
+		if (points.len() > 2)
+		{
+			lines.push(format!("    overall {:+.1}% over {}", (lastPoint.median() / first.median() - 1.0) * 100.0, plural(points.len(), "point")));
+		}
+	}
+
+	return lines.join("\n");
+}
@@ -0,0 +1,296 @@
+// The libraries the runner benchmarks side by side, like nativeLibNames in Benchmarks.cs. The Rust
+// kernels linked into the runner are always available; other builds of the same exported functions,
+// such as native_lib/benchmarks.c compiled with gcc or clang, are loaded with dlopen.
+
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::fs;
+
+use crate::kernels::{allKernels, Checksum, Kernel};
+
+// The name used for the kernels linked into the runner.
+pub(crate) const linkedName: &str = "Rust";
+
+#[cfg(unix)]
+mod ffi
+{
+	use std::ffi::{c_char, c_void};
+
+	pub(super) const RTLD_NOW: i32 = 2;
+
+	extern "C"
+	{
+		pub(super) fn dlopen(filename: *const c_char, flags: i32) -> *mut c_void;
+		pub(super) fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+		pub(super) fn dlclose(handle: *mut c_void) -> i32;
+		pub(super) fn dlerror() -> *const c_char;
+	}
+}
+
+// A shared object with all eleven benchmark_* functions resolved, in allKernels order.
+pub(crate) struct Library
+{
+	handle: *mut c_void,
+	functions: Vec<*const c_void>,
+	// Where the library was loaded from, made absolute when possible.
+	pub(crate) path: String,
+	// The compiler named in the library's .comment section, if it has one.
+	pub(crate) compiler: Option<String>
+}
+
+#[cfg(unix)]
+fn last_dl_error() -> String
+{
+	let message: *const c_char = unsafe { ffi::dlerror() };
+
+	if (message.is_null())
+	{
+		return String::from("unknown error");
+	}
+
+	return unsafe { CStr::from_ptr(message) }.to_string_lossy().into_owned();
+}
+
+// The NUL-separated strings of an ELF file's .comment section, where compilers and linkers leave
+// their version, e.g. "GCC: (Debian 12.2.0-14) 12.2.0". Both 32- and 64-bit files of either byte order
+// are understood.
+fn read_elf_comments(bytes: &[u8]) -> Option<Vec<String>>
+{
+	if (!bytes.starts_with(b"\x7fELF"))
+	{
+		return None;
+	}
+
+	let wide: bool = *bytes.get(4)? == 2;
+	let bigEndian: bool = *bytes.get(5)? == 2;
+	let read = |offset: usize, size: usize| -> Option<usize> {
+		let field: &[u8] = bytes.get(offset..offset.checked_add(size)?)?;
+		let value: u64 = if (bigEndian)
+		{
+			field.iter().fold(0, |value, byte| value << 8 | *byte as u64)
+		}
+		else
+		{
+			field.iter().rev().fold(0, |value, byte| value << 8 | *byte as u64)
+		};
+		usize::try_from(value).ok()
+	};
+
+	// (e_shoff, e_shentsize, e_shnum, e_shstrndx) and, within a section header, (sh_offset, sh_size).
+	let (sectionTable, entrySize, sectionCount, namesIndex) = if (wide)
+	{
+		(read(0x28, 8)?, read(0x3A, 2)?, read(0x3C, 2)?, read(0x3E, 2)?)
+	}
+	else
+	{
+		(read(0x20, 4)?, read(0x2E, 2)?, read(0x30, 2)?, read(0x32, 2)?)
+	};
+	let section = |index: usize| -> Option<(usize, usize, usize)> {
+		let header: usize = sectionTable.checked_add(index.checked_mul(entrySize)?)?;
+		if (wide)
+		{
+			Some((read(header, 4)?, read(header + 24, 8)?, read(header + 32, 8)?))
+		}
+		else
+		{
+			Some((read(header, 4)?, read(header + 16, 4)?, read(header + 20, 4)?))
+		}
+	};
+
+	let (_, namesOffset, _) = section(namesIndex)?;
+
+	for index in 0..sectionCount
+	{
+		let (name, offset, size) = section(index)?;
+		let nameStart: usize = namesOffset.checked_add(name)?;
+
+		if (bytes.get(nameStart..)?.starts_with(b".comment\0"))
+		{
+			let contents: &[u8] = bytes.get(offset..offset.checked_add(size)?)?;
+			let mut comments: Vec<String> = Vec::new();
+
+			for comment in contents.split(|byte| *byte == 0).filter(|comment| !comment.is_empty())
+			{
+				let comment: String = String::from_utf8_lossy(comment).into_owned();
+				if (!comments.contains(&comment))
+				{
+					comments.push(comment);
+				}
+			}
+
+			return Some(comments);
+		}
+	}
+
+	return None;
+}
+
+// The compiler that built a library. Clang builds also carry the "GCC: ..." comment of the system's
+// startup objects, so a clang comment wins when there is one.
+fn read_compiler(path: &str) -> Option<String>
+{
+	let comments: Vec<String> = read_elf_comments(&fs::read(path).ok()?)?;
+
+	return comments
+		.iter()
+		.find(|comment| comment.contains("clang version"))
+		.or_else(|| comments.first())
+		.cloned();
+}
+
+impl Library
+{
+	#[cfg(unix)]
+	pub(crate) fn open(path: &str) -> Result<Library, String>
+	{
+		// Without a slash dlopen searches the library path instead of the working directory.
+		let path: String = if (path.contains('/')) { String::from(path) } else { format!("./{}", path) };
+		let cPath: CString = CString::new(path.as_str()).map_err(|_| format!("Invalid library path '{}'", path))?;
+
+		let handle: *mut c_void = unsafe { ffi::dlopen(cPath.as_ptr(), ffi::RTLD_NOW) };
+		if (handle.is_null())
+		{
+			return Err(format!("Could not load {}: {}", path, last_dl_error()));
+		}
+
+		let mut library: Library = Library
+		{
+			handle,
+			functions: Vec::with_capacity(allKernels.len()),
+			path: fs::canonicalize(&path).map(|absolute| absolute.to_string_lossy().into_owned()).unwrap_or_else(|_| path.clone()),
+			compiler: read_compiler(&path)
+		};
+		let mut missing: Vec<&str> = Vec::new();
+
+		for kernel in allKernels
+		{
+			let symbol: CString = CString::new(kernel.symbol()).unwrap();
+			let function: *const c_void = unsafe { ffi::dlsym(handle, symbol.as_ptr()) };
+
+			if (function.is_null())
+			{
+				missing.push(kernel.symbol());
+			}
+
+			library.functions.push(function);
+		}
+
+		if (!missing.is_empty())
+		{
+			return Err(format!("{} does not export {}", path, missing.join(", ")));
+		}
+
+		return Ok(library);
+	}
+
+	#[cfg(not(unix))]
+	pub(crate) fn open(_path: &str) -> Result<Library, String>
+	{
+		return Err(String::from("--library is only supported on Unix"));
+	}
+
+	fn function(&self, kernel: Kernel) -> *const c_void
+	{
+		let index: usize = allKernels.iter().position(|other| *other == kernel).unwrap();
+
+		return self.functions[index];
+	}
+}
+
+// The handle and function pointers are only read once Library::open has returned, so the kernels can
+// be called from other threads, such as the one that measures the memory footprint.
+unsafe impl Sync for Library {}
+
+impl Drop for Library
+{
+	fn drop(&mut self)
+	{
+		#[cfg(unix)]
+		unsafe
+		{
+			ffi::dlclose(self.handle);
+		}
+	}
+}
+
+pub(crate) enum Implementation
+{
+	Linked,
+	Loaded { name: String, library: Library }
+}
+
+impl Implementation
+{
+	pub(crate) fn name(&self) -> &str
+	{
+		return match self
+		{
+			Implementation::Linked => linkedName,
+			Implementation::Loaded { name, .. } => name
+		};
+	}
+
+	// The library behind a loaded implementation, or None for the linked Rust kernels.
+	pub(crate) fn library(&self) -> Option<&Library>
+	{
+		return match self
+		{
+			Implementation::Linked => None,
+			Implementation::Loaded { library, .. } => Some(library)
+		};
+	}
+
+	pub(crate) fn run(&self, kernel: Kernel, parameters: &[u32]) -> Checksum
+	{
+		return match self
+		{
+			Implementation::Linked => kernel.run(parameters),
+			// Library::open resolved every symbol, and they all have the C signatures call expects.
+			Implementation::Loaded { library, .. } => unsafe { kernel.call(library.function(kernel), parameters) }
+		};
+	}
+}
+
+// Parses "GCC=./libbenchmarks-gcc.so". Without a name, the file name without its extension is used.
+fn parse_library_spec(spec: &str) -> (String, String)
+{
+	if let Some((name, path)) = spec.split_once('=')
+	{
+		return (String::from(name), String::from(path));
+	}
+
+	let fileName: &str = spec.rsplit('/').next().unwrap_or(spec);
+	let name: &str = fileName.split('.').next().unwrap_or(fileName);
+
+	return (String::from(name), String::from(spec));
+}
+
+// The linked Rust kernels first, unless a library takes over the name "Rust", then every library in
+// the order given.
+pub(crate) fn load_implementations(specs: &[String]) -> Result<Vec<Implementation>, String>
+{
+	let mut implementations: Vec<Implementation> = Vec::new();
+	let parsed: Vec<(String, String)> = specs.iter().map(|spec| parse_library_spec(spec)).collect();
+
+	if (!parsed.iter().any(|(name, _)| name == linkedName))
+	{
+		implementations.push(Implementation::Linked);
+	}
+
+	for (name, path) in parsed
+	{
+		if (name.is_empty())
+		{
+			return Err(format!("The library '{}' needs a name", path));
+		}
+
+		if (implementations.iter().any(|implementation| implementation.name() == name))
+		{
+			return Err(format!("More than one library is called '{}'", name));
+		}
+
+		let library: Library = Library::open(&path)?;
+		implementations.push(Implementation::Loaded { name, library });
+	}
+
+	return Ok(implementations);
+}
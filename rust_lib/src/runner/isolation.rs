@@ -0,0 +1,272 @@
+// Runs each kernel in a child process, so that a hang, a crash or a panic costs one entry instead of
+// the whole run. The runner starts itself again as "runner worker" with the kernel, its parameters,
+// the implementation and the original arguments. The worker measures that one kernel and prints the
+// measurement as JSON on stdout, which the parent reads back. A watchdog kills workers that run past
+// the timeout.
+
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::json::{object, parse_json, string, JsonValue};
+use crate::kernels::Kernel;
+use crate::measurement::Measurement;
+use crate::output::measurement_from_json;
+use crate::timer::Timer;
+
+// How much of a failed worker's stderr is kept.
+const stderrLines: usize = 20;
+
+// A kernel that didn't produce a measurement.
+pub(crate) struct Failure
+{
+	pub(crate) implementation: String,
+	pub(crate) kernel: Kernel,
+	pub(crate) parameters: Vec<u32>,
+	pub(crate) reason: String,
+	pub(crate) exitCode: Option<i32>,
+	pub(crate) signal: Option<i32>,
+	pub(crate) timedOut: bool,
+	// The last lines the worker wrote to stderr, e.g. a panic message.
+	pub(crate) stderr: String
+}
+
+// What "runner worker" needs besides the original options.
+pub(crate) struct WorkerRequest
+{
+	pub(crate) kernel: Kernel,
+	pub(crate) parameters: Vec<u32>,
+	pub(crate) implementation: String,
+	// The parent's calibrated TSC frequency, so that every worker converts cycles the same way.
+	pub(crate) tscFrequency: Option<f64>
+}
+
+#[cfg(unix)]
+mod ffi
+{
+	pub(super) const SIGKILL: i32 = 9;
+
+	extern "C"
+	{
+		pub(super) fn kill(pid: i32, sig: i32) -> i32;
+	}
+}
+
+fn signal_name(signal: i32) -> String
+{
+	let name: &str = match signal
+	{
+		4 => "SIGILL",
+		6 => "SIGABRT",
+		7 => "SIGBUS",
+		8 => "SIGFPE",
+		9 => "SIGKILL",
+		11 => "SIGSEGV",
+		15 => "SIGTERM",
+		_ => return format!("signal {}", signal)
+	};
+
+	return String::from(name);
+}
+
+#[cfg(unix)]
+fn termination_signal(status: &ExitStatus) -> Option<i32>
+{
+	use std::os::unix::process::ExitStatusExt;
+
+	return status.signal();
+}
+
+#[cfg(not(unix))]
+fn termination_signal(_status: &ExitStatus) -> Option<i32>
+{
+	return None;
+}
+
+#[cfg(unix)]
+fn kill_process(pid: u32)
+{
+	unsafe { ffi::kill(pid as i32, ffi::SIGKILL) };
+}
+
+// Without signals there is no way to stop the worker from the watchdog thread, so the timeout is
+// only reported once the worker exits by itself.
+#[cfg(not(unix))]
+fn kill_process(_pid: u32)
+{
+}
+
+fn tail(text: &str, lines: usize) -> String
+{
+	let all: Vec<&str> = text.trim_end().lines().collect();
+
+	return all[all.len().saturating_sub(lines)..].join("\n");
+}
+
+// The arguments that make the runner act as a worker for one kernel. The original arguments follow
+// "--" unchanged, so the worker sees the same options as the parent.
+fn worker_arguments(request: &WorkerRequest, originalArguments: &[String]) -> Vec<String>
+{
+	let parameters: Vec<String> = request.parameters.iter().map(u32::to_string).collect();
+	let mut arguments: Vec<String> = vec![
+		String::from("worker"),
+		String::from("--kernel-name"),
+		String::from(request.kernel.name()),
+		String::from("--parameters"),
+		parameters.join(","),
+		String::from("--implementation"),
+		request.implementation.clone()
+	];
+
+	if let Some(frequency) = request.tscFrequency
+	{
+		arguments.push(String::from("--tsc-frequency"));
+		arguments.push(frequency.to_string());
+	}
+
+	arguments.push(String::from("--"));
+	arguments.extend(originalArguments.iter().cloned());
+
+	return arguments;
+}
+
+// Waits for the worker, killing it if it is still running after `timeout`. Returns its exit status
+// and whether it was killed.
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> (std::io::Result<ExitStatus>, bool)
+{
+	let pid: u32 = child.id();
+	let (finished, watchdog) = mpsc::channel::<()>();
+
+	return std::thread::scope(|scope| {
+		// The watchdog blocks rather than polling, so that it doesn't wake up on the benchmark's CPU.
+		let killer = scope.spawn(move || {
+			if (watchdog.recv_timeout(timeout) == Err(mpsc::RecvTimeoutError::Timeout))
+			{
+				kill_process(pid);
+				return true;
+			}
+
+			return false;
+		});
+
+		let status: std::io::Result<ExitStatus> = child.wait();
+		let _ = finished.send(());
+
+		(status, killer.join().unwrap_or(false))
+	});
+}
+
+// Measures one kernel of one implementation in a worker process.
+pub(crate) fn run_isolated(request: WorkerRequest, originalArguments: &[String], timeout: Duration) -> Result<Measurement, Failure>
+{
+	let failure = |reason: String, exitCode: Option<i32>, signal: Option<i32>, timedOut: bool, stderr: String| Failure
+	{
+		implementation: request.implementation.clone(),
+		kernel: request.kernel,
+		parameters: request.parameters.clone(),
+		reason,
+		exitCode,
+		signal,
+		timedOut,
+		stderr
+	};
+
+	let executable = std::env::current_exe().map_err(|error| failure(format!("could not find the runner executable: {}", error), None, None, false, String::new()))?;
+	let mut child: Child = Command::new(executable)
+		.args(worker_arguments(&request, originalArguments))
+		.stdin(Stdio::null())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.map_err(|error| failure(format!("could not start a worker: {}", error), None, None, false, String::new()))?;
+
+	// Both pipes are drained while the worker runs, so that it never blocks on a full pipe.
+	let mut stdoutPipe = child.stdout.take().unwrap();
+	let mut stderrPipe = child.stderr.take().unwrap();
+	let (status, timedOut, stdout, stderr) = std::thread::scope(|scope| {
+		let stdoutReader = scope.spawn(move || {
+			let mut text: String = String::new();
+			let _ = stdoutPipe.read_to_string(&mut text);
+			text
+		});
+		let stderrReader = scope.spawn(move || {
+			let mut text: String = String::new();
+			let _ = stderrPipe.read_to_string(&mut text);
+			text
+		});
+
+		let (status, timedOut) = wait_with_timeout(&mut child, timeout);
+
+		(status, timedOut, stdoutReader.join().unwrap_or_default(), stderrReader.join().unwrap_or_default())
+	});
+
+	let stderr: String = tail(&stderr, stderrLines);
+	let status: ExitStatus = status.map_err(|error| failure(format!("could not wait for the worker: {}", error), None, None, false, stderr.clone()))?;
+	let signal: Option<i32> = termination_signal(&status);
+
+	if (timedOut)
+	{
+		return Err(failure(format!("timed out after {} s", timeout.as_secs_f64()), None, signal, true, stderr));
+	}
+
+	if let Some(signal) = signal
+	{
+		return Err(failure(format!("killed by {}", signal_name(signal)), None, Some(signal), false, stderr));
+	}
+
+	if (!status.success())
+	{
+		// Rust exits with 101 after a panic.
+		return Err(failure(format!("exited with status {}", status.code().unwrap_or(-1)), status.code(), None, false, stderr));
+	}
+
+	return parse_json(&stdout)
+		.and_then(|value| measurement_from_json(&value))
+		.map_err(|error| failure(format!("sent an unreadable measurement: {}", error), Some(0), None, false, stderr));
+}
+
+// The worker uses the parent's TSC frequency instead of calibrating its own.
+pub(crate) fn worker_timer(request: &WorkerRequest, requested: Option<crate::timer::TimerKind>) -> Result<Timer, String>
+{
+	return match request.tscFrequency
+	{
+		Some(frequency) => Ok(Timer::Tsc { frequency }),
+		None => Timer::create(requested)
+	};
+}
+
+pub(crate) fn failure_to_json(failure: &Failure) -> JsonValue
+{
+	let mut fields: Vec<(&str, JsonValue)> = vec![
+		("implementation", string(&failure.implementation)),
+		("kernel", string(failure.kernel.name())),
+		("symbol", string(failure.kernel.symbol())),
+		(
+			"parameters",
+			JsonValue::Object(
+				failure.kernel.parameter_names()
+					.iter()
+					.zip(failure.parameters.iter())
+					.map(|(name, value)| (String::from(*name), JsonValue::Number(*value as f64)))
+					.collect()
+			)
+		),
+		("reason", string(&failure.reason)),
+		("timed_out", JsonValue::Bool(failure.timedOut)),
+		("stderr", string(&failure.stderr))
+	];
+
+	if let Some(code) = failure.exitCode
+	{
+		fields.push(("exit_code", JsonValue::Number(code as f64)));
+	}
+
+	if let Some(signal) = failure.signal
+	{
+		fields.push(("signal", JsonValue::Number(signal as f64)));
+		fields.push(("signal_name", string(&signal_name(signal))));
+	}
+
+	return object(fields);
+}
@@ -0,0 +1,549 @@
+// Minimal JSON document model, enough for the result files the runner reads and writes.
+
+use std::fmt::Write;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum JsonValue
+{
+	Bool(bool),
+	Number(f64),
+	String(String),
+	Array(Vec<JsonValue>),
+	// Keys keep their insertion order so that written files are stable.
+	Object(Vec<(String, JsonValue)>)
+}
+
+fn escape_string(output: &mut String, value: &str)
+{
+	output.push('"');
+
+	for character in value.chars()
+	{
+		match character
+		{
+			'"' => output.push_str("\\\""),
+			'\\' => output.push_str("\\\\"),
+			'\n' => output.push_str("\\n"),
+			'\r' => output.push_str("\\r"),
+			'\t' => output.push_str("\\t"),
+			c if (c as u32) < 0x20 => write!(output, "\\u{:04x}", c as u32).unwrap(),
+			c => output.push(c)
+		}
+	}
+
+	output.push('"');
+}
+
+fn write_indent(output: &mut String, depth: usize)
+{
+	for _ in 0..depth
+	{
+		output.push('\t');
+	}
+}
+
+fn write_value(output: &mut String, value: &JsonValue, depth: usize)
+{
+	match value
+	{
+		JsonValue::Bool(value) => output.push_str(if (*value) { "true" } else { "false" }),
+		JsonValue::Number(value) =>
+		{
+			if (value.is_finite())
+			{
+				write!(output, "{}", value).unwrap();
+			}
+			else
+			{
+				output.push_str("null");
+			}
+		}
+		JsonValue::String(value) => escape_string(output, value),
+		JsonValue::Array(items) =>
+		{
+			// Arrays of plain numbers (timing samples) stay on one line.
+			if (items.iter().all(|item| matches!(item, JsonValue::Number(_))))
+			{
+				output.push('[');
+				for (i, item) in items.iter().enumerate()
+				{
+					if (i > 0)
+					{
+						output.push_str(", ");
+					}
+					write_value(output, item, depth);
+				}
+				output.push(']');
+				return;
+			}
+
+			output.push_str("[\n");
+			for (i, item) in items.iter().enumerate()
+			{
+				write_indent(output, depth + 1);
+				write_value(output, item, depth + 1);
+				output.push_str(if (i + 1 < items.len()) { ",\n" } else { "\n" });
+			}
+			write_indent(output, depth);
+			output.push(']');
+		}
+		JsonValue::Object(fields) =>
+		{
+			if (fields.is_empty())
+			{
+				output.push_str("{}");
+				return;
+			}
+
+			output.push_str("{\n");
+			for (i, (key, item)) in fields.iter().enumerate()
+			{
+				write_indent(output, depth + 1);
+				escape_string(output, key);
+				output.push_str(": ");
+				write_value(output, item, depth + 1);
+				output.push_str(if (i + 1 < fields.len()) { ",\n" } else { "\n" });
+			}
+			write_indent(output, depth);
+			output.push('}');
+		}
+	}
+}
+
+// The same as write_value without any whitespace, for one-line records.
+fn write_compact(output: &mut String, value: &JsonValue)
+{
+	match value
+	{
+		JsonValue::Array(items) =>
+		{
+			output.push('[');
+			for (i, item) in items.iter().enumerate()
+			{
+				if (i > 0)
+				{
+					output.push(',');
+				}
+				write_compact(output, item);
+			}
+			output.push(']');
+		}
+		JsonValue::Object(fields) =>
+		{
+			output.push('{');
+			for (i, (key, item)) in fields.iter().enumerate()
+			{
+				if (i > 0)
+				{
+					output.push(',');
+				}
+				escape_string(output, key);
+				output.push(':');
+				write_compact(output, item);
+			}
+			output.push('}');
+		}
+		_ => write_value(output, value, 0)
+	}
+}
+
+impl JsonValue
+{
+	pub(crate) fn to_pretty_string(&self) -> String
+	{
+		let mut output: String = String::new();
+		write_value(&mut output, self, 0);
+		output.push('\n');
+
+		return output;
+	}
+
+	// On a single line, as in a JSON-lines file.
+	pub(crate) fn to_compact_string(&self) -> String
+	{
+		let mut output: String = String::new();
+		write_compact(&mut output, self);
+
+		return output;
+	}
+}
+
+pub(crate) fn object(fields: Vec<(&str, JsonValue)>) -> JsonValue
+{
+	return JsonValue::Object(fields.into_iter().map(|(key, value)| (String::from(key), value)).collect());
+}
+
+pub(crate) fn string(value: &str) -> JsonValue
+{
+	return JsonValue::String(String::from(value));
+}
+
+impl JsonValue
+{
+	pub(crate) fn get(&self, key: &str) -> Option<&JsonValue>
+	{
+		return match self
+		{
+			JsonValue::Object(fields) => fields.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+			_ => None
+		};
+	}
+
+	pub(crate) fn as_str(&self) -> Option<&str>
+	{
+		return match self
+		{
+			JsonValue::String(value) => Some(value),
+			_ => None
+		};
+	}
+
+	pub(crate) fn as_f64(&self) -> Option<f64>
+	{
+		return match self
+		{
+			JsonValue::Number(value) => Some(*value),
+			_ => None
+		};
+	}
+
+	pub(crate) fn as_array(&self) -> Option<&[JsonValue]>
+	{
+		return match self
+		{
+			JsonValue::Array(items) => Some(items),
+			_ => None
+		};
+	}
+
+	pub(crate) fn as_object(&self) -> Option<&[(String, JsonValue)]>
+	{
+		return match self
+		{
+			JsonValue::Object(fields) => Some(fields),
+			_ => None
+		};
+	}
+}
+
+struct Parser<'a>
+{
+	bytes: &'a [u8],
+	position: usize
+}
+
+impl Parser<'_>
+{
+	fn error(&self, message: &str) -> String
+	{
+		return format!("{} at byte {}", message, self.position);
+	}
+
+	fn skip_whitespace(&mut self)
+	{
+		while (self.position < self.bytes.len() && self.bytes[self.position].is_ascii_whitespace())
+		{
+			self.position += 1;
+		}
+	}
+
+	fn peek(&self) -> Option<u8>
+	{
+		return self.bytes.get(self.position).copied();
+	}
+
+	fn expect(&mut self, byte: u8) -> Result<(), String>
+	{
+		if (self.peek() != Some(byte))
+		{
+			return Err(self.error(&format!("Expected '{}'", byte as char)));
+		}
+
+		self.position += 1;
+		return Ok(());
+	}
+
+	fn parse_literal(&mut self, literal: &str, value: JsonValue) -> Result<JsonValue, String>
+	{
+		if (!self.bytes[self.position..].starts_with(literal.as_bytes()))
+		{
+			return Err(self.error("Unexpected token"));
+		}
+
+		self.position += literal.len();
+		return Ok(value);
+	}
+
+	fn parse_number(&mut self) -> Result<JsonValue, String>
+	{
+		let start: usize = self.position;
+
+		while let Some(byte) = self.peek()
+		{
+			if (!(byte.is_ascii_digit() || byte == b'-' || byte == b'+' || byte == b'.' || byte == b'e' || byte == b'E'))
+			{
+				break;
+			}
+
+			self.position += 1;
+		}
+
+		let text: &str = std::str::from_utf8(&self.bytes[start..self.position]).unwrap();
+		return text.parse::<f64>().map(JsonValue::Number).map_err(|_| self.error("Invalid number"));
+	}
+
+	fn parse_hex(&mut self) -> Result<u32, String>
+	{
+		let digits: &[u8] = self.bytes.get(self.position..self.position + 4).ok_or_else(|| self.error("Truncated escape"))?;
+		let text: &str = std::str::from_utf8(digits).map_err(|_| self.error("Invalid escape"))?;
+		let value: u32 = u32::from_str_radix(text, 16).map_err(|_| self.error("Invalid escape"))?;
+		self.position += 4;
+
+		return Ok(value);
+	}
+
+	fn parse_string(&mut self) -> Result<String, String>
+	{
+		self.expect(b'"')?;
+
+		let mut value: String = String::new();
+
+		loop
+		{
+			let start: usize = self.position;
+
+			while let Some(byte) = self.peek()
+			{
+				if (byte == b'"' || byte == b'\\')
+				{
+					break;
+				}
+
+				self.position += 1;
+			}
+
+			value.push_str(std::str::from_utf8(&self.bytes[start..self.position]).map_err(|_| self.error("Invalid UTF-8"))?);
+
+			match self.peek()
+			{
+				Some(b'"') =>
+				{
+					self.position += 1;
+					return Ok(value);
+				}
+				Some(b'\\') =>
+				{
+					self.position += 1;
+					let escape: u8 = self.peek().ok_or_else(|| self.error("Truncated escape"))?;
+					self.position += 1;
+
+					match escape
+					{
+						b'"' => value.push('"'),
+						b'\\' => value.push('\\'),
+						b'/' => value.push('/'),
+						b'b' => value.push('\u{8}'),
+						b'f' => value.push('\u{c}'),
+						b'n' => value.push('\n'),
+						b'r' => value.push('\r'),
+						b't' => value.push('\t'),
+						b'u' =>
+						{
+							let mut code: u32 = self.parse_hex()?;
+
+							// Surrogate pair.
+							if ((0xD800..0xDC00).contains(&code) && self.bytes[self.position..].starts_with(b"\\u"))
+							{
+								self.position += 2;
+								let low: u32 = self.parse_hex()?;
+								code = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+							}
+
+							value.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+						}
+						_ => return Err(self.error("Invalid escape"))
+					}
+				}
+				_ => return Err(self.error("Unterminated string"))
+			}
+		}
+	}
+
+	fn parse_value(&mut self) -> Result<JsonValue, String>
+	{
+		self.skip_whitespace();
+
+		return match self.peek()
+		{
+			Some(b'{') =>
+			{
+				self.position += 1;
+				let mut fields: Vec<(String, JsonValue)> = Vec::new();
+
+				self.skip_whitespace();
+				if (self.peek() == Some(b'}'))
+				{
+					self.position += 1;
+					return Ok(JsonValue::Object(fields));
+				}
+
+				loop
+				{
+					self.skip_whitespace();
+					let key: String = self.parse_string()?;
+					self.skip_whitespace();
+					self.expect(b':')?;
+					fields.push((key, self.parse_value()?));
+					self.skip_whitespace();
+
+					match self.peek()
+					{
+						Some(b',') => self.position += 1,
+						Some(b'}') =>
+						{
+							self.position += 1;
+							return Ok(JsonValue::Object(fields));
+						}
+						_ => return Err(self.error("Expected ',' or '}'"))
+					}
+				}
+			}
+			Some(b'[') =>
+			{
+				self.position += 1;
+				let mut items: Vec<JsonValue> = Vec::new();
+
+				self.skip_whitespace();
+				if (self.peek() == Some(b']'))
+				{
+					self.position += 1;
+					return Ok(JsonValue::Array(items));
+				}
+
+				loop
+				{
+					items.push(self.parse_value()?);
+					self.skip_whitespace();
+
+					match self.peek()
+					{
+						Some(b',') => self.position += 1,
+						Some(b']') =>
+						{
+							self.position += 1;
+							return Ok(JsonValue::Array(items));
+						}
+						_ => return Err(self.error("Expected ',' or ']'"))
+					}
+				}
+			}
+			Some(b'"') => self.parse_string().map(JsonValue::String),
+			Some(b't') => self.parse_literal("true", JsonValue::Bool(true)),
+			Some(b'f') => self.parse_literal("false", JsonValue::Bool(false)),
+			// Non-finite numbers are written as null, so read them back as NaN.
+			Some(b'n') => self.parse_literal("null", JsonValue::Number(f64::NAN)),
+			Some(_) => self.parse_number(),
+			None => Err(self.error("Unexpected end of input"))
+		};
+	}
+}
+
+pub(crate) fn parse_json(text: &str) -> Result<JsonValue, String>
+{
+	let mut parser: Parser = Parser { bytes: text.as_bytes(), position: 0 };
+	let value: JsonValue = parser.parse_value()?;
+
+	parser.skip_whitespace();
+	if (parser.position != parser.bytes.len())
+	{
+		return Err(parser.error("Trailing characters"));
+	}
+
+	return Ok(value);
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	fn sample_document() -> JsonValue
+	{
+		return object(vec![
+			("name", string("tab\t quote\" backslash\\ newline\n bell\u{7} é 🦀")),
+			("samples", JsonValue::Array(vec![JsonValue::Number(1.5), JsonValue::Number(-2e-9), JsonValue::Number(123456789.0)])),
+			("nested", object(vec![
+				("empty_object", object(vec![])),
+				("empty_array", JsonValue::Array(vec![])),
+				("mixed", JsonValue::Array(vec![JsonValue::Bool(true), string("x"), object(vec![("deep", JsonValue::Array(vec![JsonValue::Bool(false)]))])]))
+			]))
+		]);
+	}
+
+	#[test]
+	fn pretty_and_compact_output_read_back_unchanged()
+	{
+		let document: JsonValue = sample_document();
+
+		assert_eq!(parse_json(&document.to_pretty_string()), Ok(document.clone()));
+		assert_eq!(parse_json(&document.to_compact_string()), Ok(document));
+	}
+
+	#[test]
+	fn escapes_and_surrogate_pairs_are_decoded()
+	{
+		let parsed: JsonValue = parse_json(r#""a\/b\b\f\u00e9\ud83e\udd80""#).unwrap();
+
+		assert_eq!(parsed, string("a/b\u{8}\u{c}é🦀"));
+	}
+
+	#[test]
+	fn lone_surrogates_become_replacement_characters()
+	{
+		assert_eq!(parse_json(r#""\ud83e.""#).unwrap(), string("\u{FFFD}."));
+	}
+
+	#[test]
+	fn non_finite_numbers_are_written_as_null_and_read_back_as_nan()
+	{
+		let written: String = JsonValue::Array(vec![JsonValue::Number(f64::NAN), JsonValue::Number(f64::INFINITY)]).to_compact_string();
+		assert_eq!(written, "[null,null]");
+
+		let parsed: JsonValue = parse_json(&written).unwrap();
+		assert!(parsed.as_array().unwrap().iter().all(|item| item.as_f64().unwrap().is_nan()));
+	}
+
+	#[test]
+	fn checksums_beyond_a_double_survive_as_strings()
+	{
+		// 2^64 - 1 and 2^53 + 1 can't be represented by an f64.
+		let document: JsonValue = object(vec![("checksum", string("18446744073709551615")), ("other", string("9007199254740993"))]);
+		let parsed: JsonValue = parse_json(&document.to_pretty_string()).unwrap();
+
+		assert_eq!(parsed.get("checksum").and_then(JsonValue::as_str), Some("18446744073709551615"));
+		assert_eq!(parsed.get("other").and_then(JsonValue::as_str), Some("9007199254740993"));
+	}
+
+	#[test]
+	fn truncated_input_is_an_error()
+	{
+		let text: String = sample_document().to_compact_string();
+
+		for end in 0..text.len()
+		{
+			if (text.is_char_boundary(end))
+			{
+				assert!(parse_json(&text[..end]).is_err(), "accepted {:?}", &text[..end]);
+			}
+		}
+	}
+
+	#[test]
+	fn malformed_input_is_an_error()
+	{
+		for text in ["{\"a\" 1}", "[1 2]", "tru", "\"\\x\"", "\"\\u12\"", "1 2", "{\"a\": 1,}", "-"]
+		{
+			assert!(parse_json(text).is_err(), "accepted {:?}", text);
+		}
+	}
+}
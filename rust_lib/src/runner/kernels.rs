@@ -0,0 +1,342 @@
+use std::ffi::c_void;
+use std::fmt;
+use std::hint::black_box;
+use std::mem::transmute;
+
+use benchmarks_rust::i32_benchmarks::{
+	benchmark_arcfour,
+	benchmark_fibonacci,
+	benchmark_radix,
+	benchmark_seahash,
+	benchmark_sieve_of_eratosthenes
+};
+use benchmarks_rust::f32_benchmarks::{
+	benchmark_fireflies_flocking,
+	benchmark_mandelbrot,
+	benchmark_particle_kinematics,
+	benchmark_pixar_raytracer,
+	benchmark_polynomials
+};
+use benchmarks_rust::f64_benchmarks::benchmark_nbody;
+
+// Mirrors the i32_benchmarks, f32_benchmarks and f64_benchmarks modules in the library.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Category
+{
+	I32,
+	F32,
+	F64
+}
+
+impl Category
+{
+	pub(crate) fn as_str(self) -> &'static str
+	{
+		return match self
+		{
+			Category::I32 => "i32",
+			Category::F32 => "f32",
+			Category::F64 => "f64"
+		};
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Kernel
+{
+	Fibonacci,
+	Mandelbrot,
+	NBody,
+	SieveOfEratosthenes,
+	PixarRaytracer,
+	FirefliesFlocking,
+	Polynomials,
+	ParticleKinematics,
+	Arcfour,
+	Seahash,
+	Radix
+}
+
+// Same order as Benchmarks.cs so that Results.txt files line up.
+pub(crate) const allKernels: [Kernel; 11] = [
+	Kernel::Fibonacci,
+	Kernel::Mandelbrot,
+	Kernel::NBody,
+	Kernel::SieveOfEratosthenes,
+	Kernel::PixarRaytracer,
+	Kernel::FirefliesFlocking,
+	Kernel::Polynomials,
+	Kernel::ParticleKinematics,
+	Kernel::Arcfour,
+	Kernel::Seahash,
+	Kernel::Radix
+];
+
+// The value returned by a kernel. Each exported function returns a different primitive type.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Checksum
+{
+	U32(u32),
+	I32(i32),
+	U64(u64),
+	F32(f32),
+	F64(f64)
+}
+
+impl Checksum
+{
+	pub(crate) fn type_name(self) -> &'static str
+	{
+		return match self
+		{
+			Checksum::U32(_) => "u32",
+			Checksum::I32(_) => "i32",
+			Checksum::U64(_) => "u64",
+			Checksum::F32(_) => "f32",
+			Checksum::F64(_) => "f64"
+		};
+	}
+
+	// The reverse of type_name and to_string.
+	pub(crate) fn parse(typeName: &str, text: &str) -> Option<Checksum>
+	{
+		return match typeName
+		{
+			"u32" => text.parse().ok().map(Checksum::U32),
+			"i32" => text.parse().ok().map(Checksum::I32),
+			"u64" => text.parse().ok().map(Checksum::U64),
+			"f32" => text.parse().ok().map(Checksum::F32),
+			"f64" => text.parse().ok().map(Checksum::F64),
+			_ => None
+		};
+	}
+}
+
+impl fmt::Display for Checksum
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		return match self
+		{
+			Checksum::U32(value) => write!(f, "{}", value),
+			Checksum::I32(value) => write!(f, "{}", value),
+			Checksum::U64(value) => write!(f, "{}", value),
+			Checksum::F32(value) => write!(f, "{}", value),
+			Checksum::F64(value) => write!(f, "{}", value)
+		};
+	}
+}
+
+impl Kernel
+{
+	// The name used in Results.txt, e.g. "(Rust) Sieve of Eratosthenes: ..."
+	pub(crate) fn name(self) -> &'static str
+	{
+		return match self
+		{
+			Kernel::Fibonacci => "Fibonacci",
+			Kernel::Mandelbrot => "Mandelbrot",
+			Kernel::NBody => "NBody",
+			Kernel::SieveOfEratosthenes => "Sieve of Eratosthenes",
+			Kernel::PixarRaytracer => "Pixar Raytracer",
+			Kernel::FirefliesFlocking => "Fireflies Flocking",
+			Kernel::Polynomials => "Polynomials",
+			Kernel::ParticleKinematics => "Particle Kinematics",
+			Kernel::Arcfour => "Arcfour",
+			Kernel::Seahash => "Seahash",
+			Kernel::Radix => "Radix"
+		};
+	}
+
+	pub(crate) fn from_name(name: &str) -> Option<Kernel>
+	{
+		return allKernels.iter().copied().find(|kernel| kernel.name() == name);
+	}
+
+	// The exported symbol, which is the same in the Rust and C libraries.
+	pub(crate) fn symbol(self) -> &'static str
+	{
+		return match self
+		{
+			Kernel::Fibonacci => "benchmark_fibonacci",
+			Kernel::Mandelbrot => "benchmark_mandelbrot",
+			Kernel::NBody => "benchmark_nbody",
+			Kernel::SieveOfEratosthenes => "benchmark_sieve_of_eratosthenes",
+			Kernel::PixarRaytracer => "benchmark_pixar_raytracer",
+			Kernel::FirefliesFlocking => "benchmark_fireflies_flocking",
+			Kernel::Polynomials => "benchmark_polynomials",
+			Kernel::ParticleKinematics => "benchmark_particle_kinematics",
+			Kernel::Arcfour => "benchmark_arcfour",
+			Kernel::Seahash => "benchmark_seahash",
+			Kernel::Radix => "benchmark_radix"
+		};
+	}
+
+	// The table name used in workload files, e.g. [sieve_of_eratosthenes].
+	pub(crate) fn config_name(self) -> &'static str
+	{
+		return &self.symbol()["benchmark_".len()..];
+	}
+
+	pub(crate) fn category(self) -> Category
+	{
+		return match self
+		{
+			Kernel::Fibonacci |
+			Kernel::SieveOfEratosthenes |
+			Kernel::Arcfour |
+			Kernel::Seahash |
+			Kernel::Radix => Category::I32,
+
+			Kernel::Mandelbrot |
+			Kernel::PixarRaytracer |
+			Kernel::FirefliesFlocking |
+			Kernel::Polynomials |
+			Kernel::ParticleKinematics => Category::F32,
+
+			Kernel::NBody => Category::F64
+		};
+	}
+
+	// Names of the arguments taken by the exported function, in call order.
+	pub(crate) fn parameter_names(self) -> &'static [&'static str]
+	{
+		return match self
+		{
+			Kernel::Fibonacci => &["number"],
+			Kernel::Mandelbrot => &["width", "height", "iterations"],
+			Kernel::NBody => &["advancements"],
+			Kernel::SieveOfEratosthenes => &["iterations"],
+			Kernel::PixarRaytracer => &["width", "height", "samples"],
+			Kernel::FirefliesFlocking => &["boids", "lifetime"],
+			Kernel::Polynomials => &["iterations"],
+			Kernel::ParticleKinematics => &["quantity", "iterations"],
+			Kernel::Arcfour => &["iterations"],
+			Kernel::Seahash => &["iterations"],
+			Kernel::Radix => &["iterations"]
+		};
+	}
+
+	// The argument that repeats the kernel's work a whole number of times, and what one repetition is
+	// called. Fibonacci has none, since its work grows exponentially with its argument.
+	pub(crate) fn work_unit(self) -> Option<(usize, &'static str)>
+	{
+		return match self
+		{
+			Kernel::Fibonacci => None,
+			Kernel::Mandelbrot => Some((2, "iteration")),
+			Kernel::NBody => Some((0, "advancement")),
+			Kernel::PixarRaytracer => Some((2, "sample")),
+			Kernel::FirefliesFlocking => Some((1, "lifetime step")),
+			Kernel::ParticleKinematics => Some((1, "iteration")),
+			Kernel::SieveOfEratosthenes |
+			Kernel::Polynomials |
+			Kernel::Arcfour |
+			Kernel::Seahash |
+			Kernel::Radix => Some((0, "iteration"))
+		};
+	}
+
+	// The work one run does, as the product of its size and repetition arguments, and what one unit of
+	// it is called, e.g. 1920 * 1080 * 8 pixel iterations for Mandelbrot. Dividing the time by it shows
+	// how the cost per unit changes with size, such as cache cliffs or Fireflies' quadratic neighbour
+	// loops.
+	pub(crate) fn work_amount(self, parameters: &[u32]) -> Option<(f64, &'static str)>
+	{
+		let name: &str = match self
+		{
+			Kernel::Fibonacci => return None,
+			Kernel::Mandelbrot => "pixel iteration",
+			Kernel::PixarRaytracer => "pixel sample",
+			Kernel::FirefliesFlocking => "boid step",
+			Kernel::ParticleKinematics => "particle iteration",
+			_ => self.work_unit()?.1
+		};
+
+		return Some((parameters.iter().map(|value| *value as f64).product(), name));
+	}
+
+	// The smallest value each argument can take without the Rust or C kernel failing, e.g. by indexing
+	// out of bounds.
+	pub(crate) fn parameter_minimums(self) -> &'static [u32]
+	{
+		return match self
+		{
+			// The C version decrements advancements before testing it, so 0 wraps around and never ends.
+			Kernel::NBody => &[1],
+			Kernel::Mandelbrot | Kernel::PixarRaytracer => &[0, 0, 0],
+			Kernel::FirefliesFlocking => &[0, 0],
+			Kernel::ParticleKinematics => &[1, 0],
+			_ => &[0]
+		};
+	}
+
+	// The workloads used by Benchmarks.cs.
+	pub(crate) fn default_parameters(self) -> &'static [u32]
+	{
+		return match self
+		{
+			Kernel::Fibonacci => &[46],
+			Kernel::Mandelbrot => &[1920, 1080, 8],
+			Kernel::NBody => &[100000000],
+			Kernel::SieveOfEratosthenes => &[1000000],
+			Kernel::PixarRaytracer => &[720, 480, 16],
+			Kernel::FirefliesFlocking => &[1000, 1000],
+			Kernel::Polynomials => &[10000000],
+			Kernel::ParticleKinematics => &[1000, 10000000],
+			Kernel::Arcfour => &[10000000],
+			Kernel::Seahash => &[1000000],
+			Kernel::Radix => &[1000000]
+		};
+	}
+
+	// Calls the exported function. The arguments are passed through black_box so the calls can't be
+	// constant folded into the runner.
+	pub(crate) fn run(self, parameters: &[u32]) -> Checksum
+	{
+		let p: &[u32] = black_box(parameters);
+
+		let checksum: Checksum = match self
+		{
+			Kernel::Fibonacci => Checksum::U32(benchmark_fibonacci(p[0])),
+			Kernel::Mandelbrot => Checksum::F32(benchmark_mandelbrot(p[0], p[1], p[2])),
+			Kernel::NBody => Checksum::F64(benchmark_nbody(p[0])),
+			Kernel::SieveOfEratosthenes => Checksum::U32(benchmark_sieve_of_eratosthenes(p[0])),
+			Kernel::PixarRaytracer => Checksum::F32(benchmark_pixar_raytracer(p[0], p[1], p[2])),
+			Kernel::FirefliesFlocking => Checksum::F32(benchmark_fireflies_flocking(p[0], p[1])),
+			Kernel::Polynomials => Checksum::F32(benchmark_polynomials(p[0])),
+			Kernel::ParticleKinematics => Checksum::F32(benchmark_particle_kinematics(p[0], p[1])),
+			Kernel::Arcfour => Checksum::I32(benchmark_arcfour(p[0])),
+			Kernel::Seahash => Checksum::U64(benchmark_seahash(p[0])),
+			Kernel::Radix => Checksum::I32(benchmark_radix(p[0]))
+		};
+
+		return black_box(checksum);
+	}
+
+	// Calls the same function through a pointer resolved from another library.
+	//
+	// Safety: `function` must be this kernel's exported function, with the signature declared in
+	// native_lib/benchmarks.c.
+	pub(crate) unsafe fn call(self, function: *const c_void, parameters: &[u32]) -> Checksum
+	{
+		let p: &[u32] = black_box(parameters);
+
+		let checksum: Checksum = match self
+		{
+			Kernel::Fibonacci => Checksum::U32(transmute::<*const c_void, extern "C" fn(u32) -> u32>(function)(p[0])),
+			Kernel::Mandelbrot => Checksum::F32(transmute::<*const c_void, extern "C" fn(u32, u32, u32) -> f32>(function)(p[0], p[1], p[2])),
+			Kernel::NBody => Checksum::F64(transmute::<*const c_void, extern "C" fn(u32) -> f64>(function)(p[0])),
+			Kernel::SieveOfEratosthenes => Checksum::U32(transmute::<*const c_void, extern "C" fn(u32) -> u32>(function)(p[0])),
+			Kernel::PixarRaytracer => Checksum::F32(transmute::<*const c_void, extern "C" fn(u32, u32, u32) -> f32>(function)(p[0], p[1], p[2])),
+			Kernel::FirefliesFlocking => Checksum::F32(transmute::<*const c_void, extern "C" fn(u32, u32) -> f32>(function)(p[0], p[1])),
+			Kernel::Polynomials => Checksum::F32(transmute::<*const c_void, extern "C" fn(u32) -> f32>(function)(p[0])),
+			Kernel::ParticleKinematics => Checksum::F32(transmute::<*const c_void, extern "C" fn(u32, u32) -> f32>(function)(p[0], p[1])),
+			Kernel::Arcfour => Checksum::I32(transmute::<*const c_void, extern "C" fn(u32) -> i32>(function)(p[0])),
+			Kernel::Seahash => Checksum::U64(transmute::<*const c_void, extern "C" fn(u32) -> u64>(function)(p[0])),
+			Kernel::Radix => Checksum::I32(transmute::<*const c_void, extern "C" fn(u32) -> i32>(function)(p[0]))
+		};
+
+		return black_box(checksum);
+	}
+}
@@ -0,0 +1,977 @@
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(unused_parens)]
+#![allow(clippy::needless_return)]
+
+mod affinity;
+mod allocations;
+mod baseline;
+mod calibration;
+mod chart;
+mod config;
+mod counters;
+mod diff;
+mod environment;
+mod frequency;
+mod history;
+mod implementation;
+mod isolation;
+mod json;
+mod kernels;
+mod measurement;
+mod memory;
+mod options;
+mod ordering;
+mod output;
+mod report;
+mod selection;
+mod spreadsheet;
+mod statistics;
+mod sweep;
+mod timer;
+mod validation;
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::affinity::{describe_cpu, pin_to_cpu, set_nice, set_scheduler};
+use crate::allocations::{AllocationSample, CountingAllocator};
+use crate::baseline::{compare_to_baseline, format_comparison, Comparison, Verdict};
+use crate::calibration::calibrate;
+use crate::chart::write_charts;
+use crate::config::{load_config, Config, KernelConfig};
+use crate::counters::{Counter, CounterSet};
+use crate::diff::{environment_differences, format_diff, summarise_diff};
+use crate::environment::{capture_environment, current_timestamp, Environment};
+use crate::frequency::{frequency_variation, FrequencyMonitor, FrequencySample};
+use crate::history::{append_run, build_trends, format_trend, read_history, Trend};
+use crate::implementation::{load_implementations, Implementation};
+use crate::isolation::{run_isolated, worker_timer, Failure, WorkerRequest};
+use crate::json::JsonValue;
+use crate::kernels::Kernel;
+use crate::measurement::{measure_kernel, Measurement, Recorder, Sampling};
+use crate::options::{parse_command, usage, Command, ConvertOptions, DiffOptions, HistoryOptions, Options, WorkerOptions};
+use crate::ordering::{default_seed, shuffle, Order};
+use crate::output::{measurement_to_json, read_json, read_results, timeUnit, write_csv, write_json, ResultFile, ResultRecord};
+use crate::report::{format_iso_date, write_report};
+use crate::selection::is_selected;
+use crate::spreadsheet::{write_ods, write_spreadsheet_csv};
+use crate::statistics::{median, Statistics, XorShift};
+use crate::sweep::{format_sweep, sweep_grid, write_sweep_chart};
+use crate::timer::Timer;
+use crate::validation::{validate, Validation};
+
+const resultsPath: &str = "./Results.txt";
+
+// Exit statuses of a run. They are bit flags, so a run with a regression and a checksum mismatch exits
+// with 9. 2 is left for usage and load errors.
+const exitRegression: i32 = 1;
+const exitFailure: i32 = 4;
+const exitMismatch: i32 = 8;
+
+// Only counts while --allocations has switched tracking on for a timed run.
+#[global_allocator]
+static allocator: CountingAllocator = CountingAllocator;
+
+fn format_statistics(statistics: &Statistics, unit: &str) -> String
+{
+	return format!(
+		"    min {:.0}, median {:.0}, mean {:.0}, stddev {:.0}, MAD {:.0}, 95% CI [{:.0}, {:.0}] {} ({} samples, {} outliers rejected)",
+		statistics.min,
+		statistics.median,
+		statistics.mean,
+		statistics.standardDeviation,
+		statistics.medianAbsoluteDeviation,
+		statistics.confidenceLow,
+		statistics.confidenceHigh,
+		unit,
+		statistics.samples,
+		statistics.outliers
+	);
+}
+
+fn median_of(values: &[f64]) -> f64
+{
+	let mut sorted: Vec<f64> = values.iter().copied().filter(|value| !value.is_nan()).collect();
+	sorted.sort_by(f64::total_cmp);
+
+	return median(&sorted);
+}
+
+// Median counter values over the samples, e.g. "cycles 1234, instructions 2468, IPC 2.00, ...".
+fn format_counters(measurement: &Measurement) -> String
+{
+	let mut parts: Vec<String> = measurement.counters
+		.iter()
+		.map(|(counter, values)| match counter
+		{
+			Counter::TaskClock => format!("task clock {:.3} ms", median_of(values) / 1e6),
+			_ => format!("{} {:.0}", counter.name().replace('_', " "), median_of(values))
+		})
+		.collect();
+
+	if let Some(ipc) = measurement.instructions_per_cycle()
+	{
+		parts.insert(2.min(parts.len()), format!("IPC {:.2}", median_of(&ipc)));
+	}
+
+	return format!("    {}", parts.join(", "));
+}
+
+// Median allocations per run, e.g. "    heap: 3 allocations, 48000 bytes allocated, 32000 bytes peak".
+fn format_allocations(measurement: &Measurement) -> String
+{
+	let median_by = |field: fn(&AllocationSample) -> u64| median_of(&measurement.allocations.iter().map(|sample| field(sample) as f64).collect::<Vec<f64>>());
+
+	return format!(
+		"    heap: {:.0} allocations, {:.0} bytes allocated, {:.0} bytes peak",
+		median_by(|sample| sample.count),
+		median_by(|sample| sample.bytes),
+		median_by(|sample| sample.peakBytes)
+	);
+}
+
+// "    frequency: CPU 2, 3400-4200 MHz, average 3900 MHz, up to 87.0 °C" over all samples.
+fn format_frequencies(frequencies: &[FrequencySample]) -> String
+{
+	let mut cpus: Vec<String> = frequencies.iter().map(|sample| sample.cpu.to_string()).collect();
+	cpus.dedup();
+	let lowest: f64 = frequencies.iter().map(|sample| sample.minimum).filter(|value| !value.is_nan()).fold(f64::INFINITY, f64::min);
+	let highest: f64 = frequencies.iter().map(|sample| sample.maximum).filter(|value| !value.is_nan()).fold(f64::NEG_INFINITY, f64::max);
+
+	if (lowest > highest)
+	{
+		return format!("    frequency: CPU {}, could not be read", cpus.join(", "));
+	}
+
+	let mut line: String = format!(
+		"    frequency: CPU {}, {:.0}-{:.0} MHz, average {:.0} MHz",
+		cpus.join(", "),
+		lowest,
+		highest,
+		median_of(&frequencies.iter().map(|sample| sample.average).collect::<Vec<f64>>())
+	);
+
+	if let Some(temperature) = frequencies.iter().filter_map(|sample| sample.temperature).reduce(f64::max)
+	{
+		line.push_str(&format!(", up to {:.1} °C", temperature));
+	}
+
+	return line;
+}
+
+// Starts the background frequency reader for --frequency, or says why it can't. The reader's thread
+// moves itself off the --cpu core.
+fn start_frequency_monitor(options: &Options) -> Option<FrequencyMonitor>
+{
+	if (!options.frequency)
+	{
+		return None;
+	}
+
+	return FrequencyMonitor::start(options.cpu)
+		.map_err(|message| eprintln!("Warning: {}, continuing without frequency monitoring", message))
+		.ok();
+}
+
+// "    iterations = 1234, 56.7 ns per iteration" for a calibrated kernel.
+fn format_calibration(measurement: &Measurement) -> String
+{
+	let Some((index, unit)) = measurement.kernel.work_unit() else
+	{
+		return String::from("    not calibrated, this kernel has no iteration-style parameter");
+	};
+
+	let (time, _) = measurement.time_per_unit().unwrap_or((f64::NAN, unit));
+
+	return format!("    {} = {}, {:.1} ns per {}", measurement.kernel.parameter_names()[index], measurement.parameters[index], time, unit);
+}
+
+// Opens the counters for --counters and says which ones are missing. Returns None if none at all
+// could be opened.
+fn open_counters() -> Option<CounterSet>
+{
+	let set: CounterSet = CounterSet::open();
+
+	if (set.counters().is_empty())
+	{
+		let reason: &str = set.unavailable.first().map(|(_, reason)| reason.as_str()).unwrap_or_default();
+		eprintln!("Warning: performance counters are unavailable ({}), continuing without them", reason);
+		return None;
+	}
+
+	if (!set.has_hardware())
+	{
+		let reason: &str = set.unavailable.first().map(|(_, reason)| reason.as_str()).unwrap_or_default();
+		eprintln!("Warning: hardware counters are unavailable ({}), using software counters only", reason);
+	}
+	else if (!set.unavailable.is_empty())
+	{
+		let missing: Vec<String> = set.unavailable.iter().map(|(counter, reason)| format!("{} ({})", counter.name(), reason)).collect();
+		eprintln!("Warning: some counters are unavailable: {}", missing.join(", "));
+	}
+
+	return Some(set);
+}
+
+// Loads and concatenates result files, exiting if any of them can't be read. Also returns the first
+// environment found, since Results.txt files don't have one.
+fn load_records(paths: &[String]) -> (Vec<ResultRecord>, Option<JsonValue>)
+{
+	let mut records: Vec<ResultRecord> = Vec::new();
+	let mut environment: Option<JsonValue> = None;
+
+	for path in paths
+	{
+		match read_results(path)
+		{
+			Ok(file) =>
+			{
+				for warning in &file.warnings
+				{
+					eprintln!("Warning: {}: {}", path, warning);
+				}
+				records.extend(file.records);
+				environment = environment.or(file.environment);
+			}
+			Err(message) =>
+			{
+				eprintln!("{}", message);
+				std::process::exit(2);
+			}
+		}
+	}
+
+	if (records.iter().any(|record| record.unit != records[0].unit))
+	{
+		eprintln!("Warning: the result files use different time units, so their values aren't comparable");
+	}
+
+	return (records, environment);
+}
+
+fn chart_command(options: &ConvertOptions)
+{
+	let (records, _) = load_records(&options.inputPaths);
+
+	match write_charts(&records, Path::new(&options.output))
+	{
+		Ok(paths) =>
+		{
+			for path in paths
+			{
+				println!("{}", path.display());
+			}
+		}
+		Err(error) =>
+		{
+			eprintln!("Could not write charts to {}: {}", options.output, error);
+			std::process::exit(1);
+		}
+	}
+}
+
+fn export_command(options: &ConvertOptions)
+{
+	let (records, _) = load_records(&options.inputPaths);
+	let written = if (options.output.ends_with(".csv"))
+	{
+		write_spreadsheet_csv(&options.output, &records)
+	}
+	else
+	{
+		write_ods(&options.output, &records)
+	};
+
+	if let Err(error) = written
+	{
+		eprintln!("Could not write {}: {}", options.output, error);
+		std::process::exit(1);
+	}
+
+	println!("{}", options.output);
+}
+
+// Applies --cpu, --nice and --scheduler before anything is timed. Failing to apply one of them is an
+// error rather than a warning, since the run would not be what was asked for.
+fn apply_scheduling(options: &Options)
+{
+	let mut applied: Result<(), String> = Ok(());
+
+	if let Some(cpu) = options.cpu
+	{
+		applied = applied.and_then(|_| pin_to_cpu(cpu));
+	}
+	if let Some(policy) = options.scheduler
+	{
+		applied = applied.and_then(|_| set_scheduler(policy));
+	}
+	if let Some(nice) = options.nice
+	{
+		applied = applied.and_then(|_| set_nice(nice));
+	}
+
+	if let Err(message) = applied
+	{
+		eprintln!("{}", message);
+		std::process::exit(2);
+	}
+
+	if let Some(cpu) = options.cpu
+	{
+		let (description, warnings) = describe_cpu(cpu);
+		println!("{}", description);
+		for warning in warnings
+		{
+			eprintln!("{}", warning);
+		}
+	}
+}
+
+// Prints a measurement and appends its line to Results.txt.
+fn print_measurement(options: &Options, measurement: &Measurement, results: &mut File)
+{
+	// Ticks are nanoseconds, which is what .NET's Stopwatch reports on Linux.
+	let line: String = format!(
+		"({}) {}: {:.0} ticks. Result: {}",
+		measurement.implementation,
+		measurement.kernel.name(),
+		measurement.statistics.median,
+		measurement.checksum
+	);
+
+	println!("{}", line);
+	results.write_all(line.as_bytes()).unwrap();
+	results.write_all(b"\r\n").unwrap();
+
+	match &measurement.validation
+	{
+		Validation::Mismatch { reference, expected } => eprintln!("    CHECKSUM MISMATCH: expected {} ({}), got {}", expected, reference, measurement.checksum),
+		Validation::KnownDivergence { reference, expected, reason } =>
+		{
+			println!("    known divergence: expected {} ({}), got {}: {}", expected, reference, measurement.checksum, reason);
+		}
+		_ => {}
+	}
+
+	if (options.calibrate.is_some())
+	{
+		println!("{}", format_calibration(measurement));
+	}
+
+	if let Some((time, unit)) = measurement.time_per_work().filter(|_| !options.sweeps.is_empty())
+	{
+		println!("    {:.3} ns per {}", time, unit);
+	}
+
+	if (!measurement.cycles.is_empty())
+	{
+		println!("    {:.0} TSC cycles, {:.0} ns", median_of(&measurement.cycles), measurement.statistics.median);
+	}
+
+	if (options.samples > 1)
+	{
+		println!("{}", format_statistics(&measurement.statistics, timeUnit));
+	}
+
+	if (!measurement.counters.is_empty())
+	{
+		println!("{}", format_counters(measurement));
+	}
+
+	if (!measurement.allocations.is_empty())
+	{
+		println!("{}", format_allocations(measurement));
+	}
+
+	if (!measurement.frequencies.is_empty())
+	{
+		println!("{}", format_frequencies(&measurement.frequencies));
+
+		if let Some(variation) = frequency_variation(&measurement.frequencies).filter(|variation| *variation > options.frequencyThreshold)
+		{
+			eprintln!(
+				"    Warning: the CPU frequency varied by {:.1}% during this kernel (threshold {}%), the CPU may have throttled",
+				variation,
+				options.frequencyThreshold
+			);
+		}
+	}
+
+	match &measurement.footprint
+	{
+		Some(Ok(footprint)) => println!(
+			"    memory: peak RSS {} KiB (+{} KiB during the run), stack {:.1} KiB",
+			footprint.peakRss / 1024,
+			footprint.rssGrowth / 1024,
+			footprint.stackBytes as f64 / 1024.0
+		),
+		Some(Err(message)) => eprintln!("    memory: not measured, {}", message),
+		None => {}
+	}
+}
+
+// Records a kernel that crashed, panicked or timed out in a worker.
+fn print_failure(failure: &Failure, results: &mut File)
+{
+	let line: String = format!("({}) {}: FAILED, {}", failure.implementation, failure.kernel.name(), failure.reason);
+
+	eprintln!("{}", line);
+	results.write_all(line.as_bytes()).unwrap();
+	results.write_all(b"\r\n").unwrap();
+
+	for stderrLine in failure.stderr.lines()
+	{
+		eprintln!("    | {}", stderrLine);
+	}
+}
+
+fn calibrated_parameters(options: &Options, entry: &KernelConfig, timer: &Timer) -> Vec<u32>
+{
+	return match options.calibrate
+	{
+		Some(target) => calibrate(entry.kernel, &entry.parameters, target, timer),
+		None => entry.parameters.clone()
+	};
+}
+
+// Takes the samples of every kernel and implementation in rounds, one sample of each per round, in a
+// new random order each round if there is a seed. Each pair is warmed up just before its first
+// sample. Returns the measurements kernel by kernel, implementations in order.
+fn measure_interleaved(parameterSets: &[(Kernel, Vec<u32>)], implementations: &[Implementation], sampling: &Sampling, seed: Option<u64>) -> Vec<Measurement>
+{
+	let mut recorders: Vec<Recorder> = parameterSets
+		.iter()
+		.flat_map(|(kernel, parameters)| implementations.iter().map(move |implementation| Recorder::new(implementation, *kernel, parameters, sampling)))
+		.collect();
+	let mut order: Vec<usize> = (0..recorders.len()).collect();
+	let mut random: Option<XorShift> = seed.map(XorShift::from_seed);
+	let mut position: u64 = 0;
+
+	for round in 0..sampling.samples
+	{
+		if let Some(random) = random.as_mut()
+		{
+			shuffle(&mut order, random);
+		}
+
+		for index in &order
+		{
+			if (round == 0)
+			{
+				recorders[*index].warm_up(sampling);
+			}
+
+			recorders[*index].sample(sampling, position);
+			position += 1;
+		}
+	}
+
+	return recorders.into_iter().map(|recorder| recorder.finish(sampling)).collect();
+}
+
+// Validates, prints and keeps the results of one kernel.
+fn record_group(options: &Options, mut group: Vec<Measurement>, groupFailures: Vec<Failure>, results: &mut File, measurements: &mut Vec<Measurement>, failures: &mut Vec<Failure>)
+{
+	if (options.validate)
+	{
+		validate(&mut group, options.reference.as_deref());
+	}
+
+	for measurement in &group
+	{
+		print_measurement(options, measurement, results);
+	}
+	for failure in &groupFailures
+	{
+		print_failure(failure, results);
+	}
+
+	measurements.extend(group);
+	failures.extend(groupFailures);
+}
+
+fn run_command(options: &Options)
+{
+	let config: Config = match &options.configPath
+	{
+		Some(path) => load_config(path).unwrap_or_else(|message|
+		{
+			eprintln!("{}", message);
+			std::process::exit(2);
+		}),
+		None => Config::default()
+	};
+
+	let selected: Vec<&KernelConfig> = config.kernels
+		.iter()
+		.filter(|entry| entry.enabled && is_selected(entry.kernel, &options.include, &options.exclude))
+		.collect();
+
+	if (selected.is_empty())
+	{
+		eprintln!("No kernels left to run after applying the workload file, --kernel and --exclude");
+		std::process::exit(2);
+	}
+
+	// A sweep replaces the kernel's one workload with a grid of them.
+	let sweepGrid: Option<Vec<Vec<u32>>> = if (options.sweeps.is_empty())
+	{
+		None
+	}
+	else
+	{
+		if (selected.len() != 1)
+		{
+			eprintln!("--sweep runs one kernel, but {} are selected. Pick one with --kernel", selected.len());
+			std::process::exit(2);
+		}
+
+		Some(sweep_grid(selected[0].kernel, &selected[0].parameters, &options.sweeps).unwrap_or_else(|message|
+		{
+			eprintln!("{}", message);
+			std::process::exit(2);
+		}))
+	};
+
+	// Load the baseline up front so that a bad path doesn't waste a full run.
+	let baseline: Option<ResultFile> = options.baselinePath.as_deref().map(|path| match read_json(path)
+	{
+		Ok(file) => file,
+		Err(message) =>
+		{
+			eprintln!("{}", message);
+			std::process::exit(2);
+		}
+	});
+
+	let implementations: Vec<Implementation> = load_implementations(&options.libraries).unwrap_or_else(|message|
+	{
+		eprintln!("{}", message);
+		std::process::exit(2);
+	});
+
+	if let Some(reference) = &options.reference
+	{
+		if (!implementations.iter().any(|implementation| implementation.name() == reference))
+		{
+			let names: Vec<&str> = implementations.iter().map(Implementation::name).collect();
+			eprintln!("--reference {} is not one of the implementations being run ({})", reference, names.join(", "));
+			std::process::exit(2);
+		}
+	}
+
+	let frequencyMonitor: Option<FrequencyMonitor> = start_frequency_monitor(options);
+	apply_scheduling(options);
+	let counterSet: Option<CounterSet> = if (options.counters) { open_counters() } else { None };
+	let timer: Timer = Timer::create(options.timer).unwrap_or_else(|message|
+	{
+		eprintln!("{}", message);
+		std::process::exit(2);
+	});
+
+	let seed: Option<u64> = if (options.order == Order::Random) { Some(options.seed.unwrap_or_else(default_seed)) } else { None };
+	let environment: Environment = capture_environment(&timer, options.order, seed, &implementations);
+	println!("{}", environment.summary());
+	println!("{}", timer.description());
+	match seed
+	{
+		Some(seed) => println!("Order: random, seed {}", seed),
+		None => println!("Order: {}", options.order.as_str())
+	}
+
+	let mut results: File = OpenOptions::new()
+		.append(true)
+		.create(true)
+		.open(resultsPath)
+		.unwrap();
+
+	results.write_all(b"\r\nBenchmark Results:\r\n").unwrap();
+
+	let mut measurements: Vec<Measurement> = Vec::new();
+	let mut failures: Vec<Failure> = Vec::new();
+	let arguments: Vec<String> = std::env::args().skip(1).collect();
+	let sampling: Sampling = Sampling
+	{
+		warmups: options.warmups,
+		samples: options.samples,
+		timer: &timer,
+		counterSet: counterSet.as_ref(),
+		trackAllocations: options.allocations,
+		measureMemory: options.memory,
+		frequencyMonitor: frequencyMonitor.as_ref()
+	};
+
+	let parameterSets: Vec<(Kernel, Vec<u32>)> = match &sweepGrid
+	{
+		Some(grid) => grid.iter().map(|parameters| (selected[0].kernel, parameters.clone())).collect(),
+		None => selected.iter().map(|entry| (entry.kernel, calibrated_parameters(options, entry, &timer))).collect()
+	};
+
+	if (options.order != Order::Sequential)
+	{
+		let mut finished = measure_interleaved(&parameterSets, &implementations, &sampling, seed).into_iter();
+
+		// Reported kernel by kernel, in the same layout as the sequential order.
+		for _ in &parameterSets
+		{
+			let group: Vec<Measurement> = finished.by_ref().take(implementations.len()).collect();
+			record_group(options, group, Vec::new(), &mut results, &mut measurements, &mut failures);
+		}
+	}
+	else
+	{
+		let mut nextPosition: u64 = 0;
+		for (kernel, parameters) in &parameterSets
+		{
+			let kernel: Kernel = *kernel;
+
+			// Every implementation runs one kernel before the next kernel starts, like Benchmarks.cs.
+			let mut group: Vec<Measurement> = Vec::new();
+			let mut groupFailures: Vec<Failure> = Vec::new();
+			for implementation in &implementations
+			{
+				if (!options.isolate)
+				{
+					group.push(measure_kernel(implementation, kernel, parameters, &sampling));
+					continue;
+				}
+
+				let request: WorkerRequest = WorkerRequest
+				{
+					kernel,
+					parameters: parameters.clone(),
+					implementation: String::from(implementation.name()),
+					tscFrequency: timer.tsc_frequency()
+				};
+
+				match run_isolated(request, &arguments, options.timeout)
+				{
+					Ok(measurement) => group.push(measurement),
+					Err(failure) => groupFailures.push(failure)
+				}
+			}
+
+			// Each measurement counts its positions from 0.
+			for measurement in group.iter_mut()
+			{
+				measurement.positions.iter_mut().for_each(|position| *position += nextPosition);
+				nextPosition += measurement.positions.len() as u64;
+			}
+
+			record_group(options, group, groupFailures, &mut results, &mut measurements, &mut failures);
+		}
+	}
+
+	if let Some(grid) = &sweepGrid
+	{
+		println!("\nSweep of {}:", selected[0].kernel.name());
+		for line in format_sweep(&measurements, &options.sweeps)
+		{
+			println!("{}", line);
+		}
+
+		if let Some(path) = &options.sweepChartPath
+		{
+			if let Err(error) = write_sweep_chart(path, selected[0].kernel, grid, &options.sweeps, &measurements)
+			{
+				eprintln!("Could not write {}: {}", path, error);
+				std::process::exit(1);
+			}
+		}
+	}
+
+	if let Some(path) = &options.jsonPath
+	{
+		if let Err(error) = write_json(path, &environment, &measurements, &failures)
+		{
+			eprintln!("Could not write {}: {}", path, error);
+			std::process::exit(1);
+		}
+	}
+
+	if let Some(path) = &options.csvPath
+	{
+		if let Err(error) = write_csv(path, &environment, &measurements)
+		{
+			eprintln!("Could not write {}: {}", path, error);
+			std::process::exit(1);
+		}
+	}
+
+	// Calibration and sweeps measure something other than the workload, so they stay out of the
+	// history unless asked for.
+	let recordHistory: bool = options.recordHistory.unwrap_or(config.history && options.calibrate.is_none() && options.sweeps.is_empty());
+	if (recordHistory)
+	{
+		if let Err(error) = append_run(&options.historyPath, &environment, &measurements, &failures)
+		{
+			eprintln!("Could not add the run to {}: {}", options.historyPath, error);
+			std::process::exit(1);
+		}
+	}
+
+	let mut exitCode: i32 = 0;
+
+	if let Some(baseline) = &baseline
+	{
+		let current: Vec<ResultRecord> = measurements
+			.iter()
+			.map(ResultRecord::from_measurement)
+			.collect();
+		let (comparisons, notes) = compare_to_baseline(&baseline.records, &current, options.thresholdPercent);
+
+		let baselineRustc: &str = baseline.environment
+			.as_ref()
+			.and_then(|environment| environment.get("rustc_version"))
+			.and_then(JsonValue::as_str)
+			.unwrap_or("unknown rustc");
+
+		println!(
+			"\nComparison with {} ({}, threshold {}%):",
+			options.baselinePath.as_deref().unwrap_or_default(),
+			baselineRustc,
+			options.thresholdPercent
+		);
+		for comparison in &comparisons
+		{
+			println!("{}", format_comparison(comparison));
+		}
+		for note in &notes
+		{
+			println!("{}", note);
+		}
+
+		let regressions: Vec<&Comparison> = comparisons.iter().filter(|comparison| comparison.verdict == Verdict::Regression).collect();
+		if (!regressions.is_empty())
+		{
+			eprintln!("{} kernel(s) regressed past {}%", regressions.len(), options.thresholdPercent);
+			exitCode |= exitRegression;
+		}
+	}
+
+	let mismatches: Vec<&Measurement> = measurements
+		.iter()
+		.filter(|measurement| matches!(measurement.validation, Validation::Mismatch { .. }))
+		.collect();
+	if (!mismatches.is_empty())
+	{
+		eprintln!("\nCHECKSUM MISMATCHES:");
+		for measurement in &mismatches
+		{
+			if let Validation::Mismatch { reference, expected } = &measurement.validation
+			{
+				eprintln!("    ({}) {}: got {}, expected {} ({})", measurement.implementation, measurement.kernel.name(), measurement.checksum, expected, reference);
+			}
+		}
+		exitCode |= exitMismatch;
+	}
+
+	if (!failures.is_empty())
+	{
+		eprintln!("\nFAILED KERNELS:");
+		for failure in &failures
+		{
+			eprintln!("    ({}) {}: {}", failure.implementation, failure.kernel.name(), failure.reason);
+		}
+		exitCode |= exitFailure;
+	}
+
+	if (exitCode != 0)
+	{
+		std::process::exit(exitCode);
+	}
+}
+
+// Measures one kernel for a parent runner with --isolate and prints the measurement as JSON. The
+// parent already applied --cpu, --nice and --scheduler, which the worker inherits.
+fn worker_command(worker: &WorkerOptions)
+{
+	let (options, request) = (&worker.options, &worker.request);
+	let fail = |message: String| -> !
+	{
+		eprintln!("{}", message);
+		std::process::exit(2);
+	};
+
+	let implementations: Vec<Implementation> = load_implementations(&options.libraries).unwrap_or_else(|message| fail(message));
+	let implementation: &Implementation = implementations
+		.iter()
+		.find(|implementation| implementation.name() == request.implementation)
+		.unwrap_or_else(|| fail(format!("Unknown implementation '{}'", request.implementation)));
+	// The parent has already warned about any counters that are missing.
+	let counterSet: Option<CounterSet> = if (options.counters) { Some(CounterSet::open()).filter(|set| !set.counters().is_empty()) } else { None };
+	let timer: Timer = worker_timer(request, options.timer).unwrap_or_else(|message| fail(message));
+	// The parent has also warned if the frequency can't be read. The monitor's thread leaves the
+	// inherited --cpu core by itself.
+	let frequencyMonitor: Option<FrequencyMonitor> = if (options.frequency) { FrequencyMonitor::start(options.cpu).ok() } else { None };
+
+	let sampling: Sampling = Sampling
+	{
+		warmups: options.warmups,
+		samples: options.samples,
+		timer: &timer,
+		counterSet: counterSet.as_ref(),
+		trackAllocations: options.allocations,
+		measureMemory: options.memory,
+		frequencyMonitor: frequencyMonitor.as_ref()
+	};
+	let measurement: Measurement = measure_kernel(implementation, request.kernel, &request.parameters, &sampling);
+
+	println!("{}", measurement_to_json(&measurement).to_pretty_string());
+}
+
+// Lines up the kernels of two result files. The old file plays the part of the baseline.
+fn diff_command(options: &DiffOptions)
+{
+	let read = |path: &str| {
+		let file: ResultFile = read_results(path).unwrap_or_else(|message|
+		{
+			eprintln!("{}", message);
+			std::process::exit(2);
+		});
+		for warning in &file.warnings
+		{
+			eprintln!("Warning: {}: {}", path, warning);
+		}
+		file
+	};
+	let (old, new) = (read(&options.oldPath), read(&options.newPath));
+	let describe = |path: &str, file: &ResultFile| match &file.environment
+	{
+		Some(environment) => format!(
+			"{} ({}, {})",
+			path,
+			environment.get("rustc_version").and_then(JsonValue::as_str).unwrap_or("unknown rustc"),
+			format_iso_date(environment.get("timestamp").and_then(JsonValue::as_f64).unwrap_or(0.0) as u64)
+		),
+		None => String::from(path)
+	};
+
+	println!("Old (baseline): {}", describe(&options.oldPath, &old));
+	println!("New: {}", describe(&options.newPath, &new));
+
+	if let (Some(oldEnvironment), Some(newEnvironment)) = (&old.environment, &new.environment)
+	{
+		let differences: Vec<String> = environment_differences(oldEnvironment, newEnvironment);
+		if (!differences.is_empty())
+		{
+			println!("Environment differences:");
+			for difference in &differences
+			{
+				println!("{}", difference);
+			}
+		}
+	}
+
+	let (comparisons, mut notes) = compare_to_baseline(&old.records, &new.records, options.thresholdPercent);
+	for record in old.records.iter().filter(|record| !new.records.iter().any(|other| other.same_workload(record)))
+	{
+		notes.push(format!("({}) {}: only in the baseline", record.implementation, record.kernel));
+	}
+
+	println!();
+	if (comparisons.is_empty())
+	{
+		println!("No kernels in common");
+	}
+	else
+	{
+		for line in format_diff(&comparisons)
+		{
+			println!("{}", line);
+		}
+	}
+
+	for note in &notes
+	{
+		println!("{}", note);
+	}
+
+	if (!comparisons.is_empty())
+	{
+		println!("\n{}", summarise_diff(&comparisons));
+	}
+}
+
+fn history_command(options: &HistoryOptions)
+{
+	let (runs, warnings) = read_history(&options.historyPath).unwrap_or_else(|message|
+	{
+		eprintln!("{}", message);
+		std::process::exit(2);
+	});
+
+	for warning in &warnings
+	{
+		eprintln!("{}", warning);
+	}
+
+	// Kernels the runner doesn't know, e.g. from a newer version, only show when nothing is filtered.
+	let selected = |record: &ResultRecord| -> bool {
+		let kernelSelected: bool = match Kernel::from_name(&record.kernel)
+		{
+			Some(kernel) => is_selected(kernel, &options.include, &options.exclude),
+			None => options.include.is_empty() && options.exclude.is_empty()
+		};
+
+		return kernelSelected && options.implementation.as_ref().is_none_or(|name| *name == record.implementation);
+	};
+	let trends: Vec<Trend> = build_trends(&runs, selected, options.byToolchain);
+
+	if (trends.is_empty())
+	{
+		eprintln!("No matching kernels in the {} recorded runs in {}", runs.len(), options.historyPath);
+		std::process::exit(2);
+	}
+
+	let blocks: Vec<String> = trends.iter().map(|trend| format_trend(trend, options.last)).collect();
+	println!("{}", blocks.join("\n\n"));
+}
+
+fn report_command(options: &ConvertOptions)
+{
+	let (records, environment) = load_records(&options.inputPaths);
+	let timestamp: u64 = environment
+		.as_ref()
+		.and_then(|environment| environment.get("timestamp"))
+		.and_then(JsonValue::as_f64)
+		.map(|timestamp| timestamp as u64)
+		.unwrap_or_else(current_timestamp);
+	let output: String = if (options.output.is_empty())
+	{
+		format!("benchmark_results_{}", format_iso_date(timestamp))
+	}
+	else
+	{
+		options.output.clone()
+	};
+
+	if let Err(error) = write_report(Path::new(&output), &records, environment.as_ref(), timestamp)
+	{
+		eprintln!("Could not write the report to {}: {}", output, error);
+		std::process::exit(1);
+	}
+
+	println!("{}", Path::new(&output).join("README.md").display());
+}
+
+fn main()
+{
+	match parse_command(std::env::args().skip(1))
+	{
+		Ok(Command::Run(options)) => run_command(&options),
+		Ok(Command::Worker(worker)) => worker_command(&worker),
+		Ok(Command::History(options)) => history_command(&options),
+		Ok(Command::Diff(options)) => diff_command(&options),
+		Ok(Command::Chart(options)) => chart_command(&options),
+		Ok(Command::Export(options)) => export_command(&options),
+		Ok(Command::Report(options)) => report_command(&options),
+		Err(message) =>
+		{
+			eprintln!("{}\n\n{}", message, usage);
+			std::process::exit(2);
+		}
+	}
+}
@@ -0,0 +1,230 @@
+use crate::allocations::{start_tracking, stop_tracking, AllocationSample};
+use crate::counters::{Counter, CounterSet};
+use crate::frequency::{FrequencyMonitor, FrequencySample};
+use crate::implementation::Implementation;
+use crate::kernels::{Checksum, Kernel};
+use crate::memory::{measure_footprint, Footprint};
+use crate::statistics::{compute_statistics, Statistics};
+use crate::timer::Timer;
+use crate::validation::Validation;
+
+// The outcome of timing one kernel with one set of parameters.
+pub(crate) struct Measurement
+{
+	pub(crate) implementation: String,
+	pub(crate) kernel: Kernel,
+	pub(crate) parameters: Vec<u32>,
+	pub(crate) checksum: Checksum,
+	pub(crate) samples: Vec<f64>,
+	// The same samples in TSC cycles, when timed with the TSC.
+	pub(crate) cycles: Vec<f64>,
+	pub(crate) statistics: Statistics,
+	// One value per sample for each counter that could be opened.
+	pub(crate) counters: Vec<(Counter, Vec<f64>)>,
+	// One sample per timed run with --allocations, for the kernels linked into the runner.
+	pub(crate) allocations: Vec<AllocationSample>,
+	// The CPU frequency during each timed run with --frequency, where the CPU reports it.
+	pub(crate) frequencies: Vec<FrequencySample>,
+	// From one more, untimed run with --memory, or why it couldn't be measured.
+	pub(crate) footprint: Option<Result<Footprint, String>>,
+	// Where each sample was taken among all the samples of the run, to analyse drift.
+	pub(crate) positions: Vec<u64>,
+	pub(crate) validation: Validation
+}
+
+impl Measurement
+{
+	pub(crate) fn counter_values(&self, counter: Counter) -> Option<&[f64]>
+	{
+		return self.counters.iter().find(|(other, _)| *other == counter).map(|(_, values)| values.as_slice());
+	}
+
+	// Median nanoseconds per repetition of the kernel's work, e.g. per Sieve of Eratosthenes
+	// iteration, with the unit's name.
+	pub(crate) fn time_per_unit(&self) -> Option<(f64, &'static str)>
+	{
+		let (index, unit) = self.kernel.work_unit()?;
+		let count: u32 = self.parameters[index];
+
+		if (count == 0)
+		{
+			return None;
+		}
+
+		return Some((self.statistics.median / count as f64, unit));
+	}
+
+	// Median nanoseconds per unit of Kernel::work_amount, with the unit's name.
+	pub(crate) fn time_per_work(&self) -> Option<(f64, &'static str)>
+	{
+		let (amount, unit) = self.kernel.work_amount(&self.parameters)?;
+
+		if (amount == 0.0)
+		{
+			return None;
+		}
+
+		return Some((self.statistics.median / amount, unit));
+	}
+
+	// Instructions per cycle for each sample, if both hardware counters were available.
+	pub(crate) fn instructions_per_cycle(&self) -> Option<Vec<f64>>
+	{
+		let cycles: &[f64] = self.counter_values(Counter::Cycles)?;
+		let instructions: &[f64] = self.counter_values(Counter::Instructions)?;
+
+		return Some(instructions.iter().zip(cycles).map(|(instructions, cycles)| instructions / cycles).collect());
+	}
+}
+
+// How every kernel is sampled, and what is recorded besides the time.
+pub(crate) struct Sampling<'a>
+{
+	pub(crate) warmups: usize,
+	pub(crate) samples: usize,
+	pub(crate) timer: &'a Timer,
+	pub(crate) counterSet: Option<&'a CounterSet>,
+	pub(crate) trackAllocations: bool,
+	pub(crate) measureMemory: bool,
+	pub(crate) frequencyMonitor: Option<&'a FrequencyMonitor>
+}
+
+// A measurement collected one sample at a time, so that samples of different kernels and
+// implementations can be interleaved.
+pub(crate) struct Recorder<'a>
+{
+	implementation: &'a Implementation,
+	kernel: Kernel,
+	parameters: Vec<u32>,
+	checksum: Checksum,
+	times: Vec<f64>,
+	cycles: Vec<f64>,
+	counters: Vec<(Counter, Vec<f64>)>,
+	allocations: Vec<AllocationSample>,
+	frequencies: Vec<FrequencySample>,
+	positions: Vec<u64>
+}
+
+impl<'a> Recorder<'a>
+{
+	pub(crate) fn new(implementation: &'a Implementation, kernel: Kernel, parameters: &[u32], sampling: &Sampling) -> Recorder<'a>
+	{
+		return Recorder
+		{
+			implementation,
+			kernel,
+			parameters: parameters.to_vec(),
+			checksum: Checksum::U32(0),
+			times: Vec::with_capacity(sampling.samples),
+			cycles: Vec::new(),
+			counters: sampling.counterSet
+				.map(|set| set.counters().into_iter().map(|counter| (counter, Vec::with_capacity(sampling.samples))).collect())
+				.unwrap_or_default(),
+			allocations: Vec::new(),
+			frequencies: Vec::new(),
+			positions: Vec::with_capacity(sampling.samples)
+		};
+	}
+
+	pub(crate) fn warm_up(&mut self, sampling: &Sampling)
+	{
+		for _ in 0..sampling.warmups
+		{
+			self.checksum = self.implementation.run(self.kernel, &self.parameters);
+		}
+	}
+
+	// Takes one timed sample. `position` is its place among all the samples of the run. The counters
+	// and the frequency monitor, if any, are started just outside the timed region so that their
+	// system calls aren't timed.
+	pub(crate) fn sample(&mut self, sampling: &Sampling, position: u64)
+	{
+		let (timer, counterSet) = (sampling.timer, sampling.counterSet);
+		let trackAllocations: bool = sampling.trackAllocations && matches!(self.implementation, Implementation::Linked);
+
+		let monitoredCpu: Option<usize> = sampling.frequencyMonitor.map(FrequencyMonitor::begin);
+		if let Some(set) = counterSet
+		{
+			set.start();
+		}
+		if (trackAllocations)
+		{
+			start_tracking();
+		}
+
+		let start: u64 = timer.start();
+		self.checksum = self.implementation.run(self.kernel, &self.parameters);
+		let ticks: u64 = timer.stop().saturating_sub(start);
+
+		if (trackAllocations)
+		{
+			self.allocations.push(stop_tracking());
+		}
+
+		self.times.push(timer.to_nanoseconds(ticks));
+		if (timer.tsc_frequency().is_some())
+		{
+			self.cycles.push(ticks as f64);
+		}
+
+		if let Some(set) = counterSet
+		{
+			for ((_, values), reading) in self.counters.iter_mut().zip(set.stop())
+			{
+				values.push(reading);
+			}
+		}
+		if let (Some(monitor), Some(cpu)) = (sampling.frequencyMonitor, monitoredCpu)
+		{
+			self.frequencies.push(monitor.end(cpu));
+		}
+
+		self.positions.push(position);
+	}
+
+	// Runs the --memory footprint run, if asked for, and computes the statistics.
+	pub(crate) fn finish(self, sampling: &Sampling) -> Measurement
+	{
+		let (implementation, kernel) = (self.implementation, self.kernel);
+		let footprint: Option<Result<Footprint, String>> = if (sampling.measureMemory)
+		{
+			Some(measure_footprint(|| { implementation.run(kernel, &self.parameters); }))
+		}
+		else
+		{
+			None
+		};
+
+		return Measurement
+		{
+			implementation: String::from(implementation.name()),
+			kernel,
+			parameters: self.parameters,
+			checksum: self.checksum,
+			statistics: compute_statistics(&self.times),
+			samples: self.times,
+			cycles: self.cycles,
+			counters: self.counters,
+			allocations: self.allocations,
+			frequencies: self.frequencies,
+			positions: self.positions,
+			footprint,
+			validation: Validation::Unchecked
+		};
+	}
+}
+
+// Runs the kernel `warmups` times untimed, then `samples` times timed. Samples are nanoseconds and
+// their positions count from 0.
+pub(crate) fn measure_kernel(implementation: &Implementation, kernel: Kernel, parameters: &[u32], sampling: &Sampling) -> Measurement
+{
+	let mut recorder: Recorder = Recorder::new(implementation, kernel, parameters, sampling);
+
+	recorder.warm_up(sampling);
+	for position in 0..sampling.samples
+	{
+		recorder.sample(sampling, position as u64);
+	}
+
+	return recorder.finish(sampling);
+}
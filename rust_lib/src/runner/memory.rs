@@ -0,0 +1,176 @@
+// Memory footprint of one kernel run: how far the process's resident set grew and how deep the stack
+// went. The kernel runs once more, untimed, on a thread with a known stack. The unused part of that
+// stack is filled with a pattern first, and afterwards the deepest overwritten word shows the
+// high-water mark. Peak RSS comes from VmHWM, which /proc/self/clear_refs resets before the run.
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Footprint
+{
+	// VmHWM after the run, for the whole process.
+	pub(crate) peakRss: u64,
+	// How far the peak rose above the resident set at the start of the run.
+	pub(crate) rssGrowth: u64,
+	// Stack used by the kernel, measured from the frame that called it.
+	pub(crate) stackBytes: u64
+}
+
+#[cfg(target_os = "linux")]
+mod ffi
+{
+	use std::ffi::c_void;
+
+	// glibc's pthread_attr_t is 56 bytes on 64-bit targets.
+	#[repr(C)]
+	pub(super) struct PthreadAttr
+	{
+		pub(super) opaque: [u64; 8]
+	}
+
+	extern "C"
+	{
+		pub(super) fn pthread_self() -> usize;
+		pub(super) fn pthread_getattr_np(thread: usize, attr: *mut PthreadAttr) -> i32;
+		pub(super) fn pthread_attr_getstack(attr: *const PthreadAttr, stackaddr: *mut *mut c_void, stacksize: *mut usize) -> i32;
+		pub(super) fn pthread_attr_destroy(attr: *mut PthreadAttr) -> i32;
+	}
+}
+
+#[cfg(target_os = "linux")]
+mod linux
+{
+	use std::fs;
+	use std::hint::black_box;
+	use std::ptr;
+
+	use super::{ffi, Footprint};
+
+	// Big enough for Seahash's 128 KiB array being copied into each call, with plenty to spare.
+	const stackSize: usize = 16 << 20;
+	const stackPattern: u64 = 0x5A5A_A5A5_5A5A_A5A5;
+	// Left unpainted below the painting and scanning functions' own frames, including the red zone.
+	const frameMargin: usize = 512;
+	const pageSize: usize = 4096;
+
+	// A field of /proc/self/status such as "VmHWM:   1392 kB", in bytes.
+	fn status_field(name: &str) -> Option<u64>
+	{
+		let status: String = fs::read_to_string("/proc/self/status").ok()?;
+		let line: &str = status.lines().find(|line| line.starts_with(name))?;
+		let kilobytes: u64 = line[name.len()..].trim_start_matches(':').trim().trim_end_matches("kB").trim().parse().ok()?;
+
+		return Some(kilobytes * 1024);
+	}
+
+	// The lowest usable address of the calling thread's stack.
+	fn stack_low() -> Option<usize>
+	{
+		let mut attributes: ffi::PthreadAttr = ffi::PthreadAttr { opaque: [0; 8] };
+		let mut address: *mut std::ffi::c_void = ptr::null_mut();
+		let mut size: usize = 0;
+
+		unsafe
+		{
+			if (ffi::pthread_getattr_np(ffi::pthread_self(), &mut attributes) != 0)
+			{
+				return None;
+			}
+
+			let result: i32 = ffi::pthread_attr_getstack(&attributes, &mut address, &mut size);
+			ffi::pthread_attr_destroy(&mut attributes);
+
+			if (result != 0)
+			{
+				return None;
+			}
+		}
+
+		// One page of slack in case the guard page is counted as part of the stack.
+		return Some(address as usize + pageSize);
+	}
+
+	fn stack_pointer() -> usize
+	{
+		let marker: u8 = 0;
+
+		return black_box(&marker) as *const u8 as usize;
+	}
+
+	// Fills the stack from `low` up to just below this function's frame, and returns where the
+	// painting stops.
+	#[inline(never)]
+	fn paint_stack(low: usize) -> usize
+	{
+		let top: usize = (stack_pointer() - frameMargin) & !7;
+		let mut address: usize = low;
+
+		while (address < top)
+		{
+			unsafe { ptr::write_volatile(address as *mut u64, stackPattern) };
+			address += 8;
+		}
+
+		return top;
+	}
+
+	// The lowest address below `top` that no longer holds the pattern.
+	#[inline(never)]
+	fn deepest_write(low: usize, top: usize) -> usize
+	{
+		let mut address: usize = low;
+
+		while (address < top && unsafe { ptr::read_volatile(address as *const u64) } == stackPattern)
+		{
+			address += 8;
+		}
+
+		return address;
+	}
+
+	fn clear_peak_rss() -> bool
+	{
+		return fs::write("/proc/self/clear_refs", "5").is_ok();
+	}
+
+	// Runs the kernel on its own thread. Threads inherit the runner's CPU affinity.
+	pub(crate) fn measure_footprint(run: impl FnOnce() + Send) -> Result<Footprint, String>
+	{
+		return std::thread::scope(|scope| {
+			let handle = std::thread::Builder::new()
+				.stack_size(stackSize)
+				.spawn_scoped(scope, move || -> Result<Footprint, String> {
+					let low: usize = stack_low().ok_or_else(|| String::from("Could not find the thread's stack"))?;
+					let base: usize = stack_pointer();
+
+					// The first painting makes the whole stack resident, so that it doesn't count towards
+					// the growth. Reading /proc leaves its own frames in the painted area, so the stack is
+					// painted again just before the run and scanned straight after it.
+					paint_stack(low);
+					if (!clear_peak_rss())
+					{
+						return Err(String::from("Could not reset the peak RSS through /proc/self/clear_refs"));
+					}
+					let startRss: u64 = status_field("VmRSS").unwrap_or(0);
+
+					let top: usize = paint_stack(low);
+					run();
+					let deepest: usize = deepest_write(low, top);
+
+					let peakRss: u64 = status_field("VmHWM").ok_or_else(|| String::from("Could not read VmHWM from /proc/self/status"))?;
+
+					Ok(Footprint { peakRss, rssGrowth: peakRss.saturating_sub(startRss), stackBytes: base.saturating_sub(deepest) as u64 })
+				})
+				.map_err(|error| format!("Could not start a thread for the footprint run: {}", error))?;
+
+			return handle.join().unwrap_or_else(|_| Err(String::from("The kernel panicked")));
+		});
+	}
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) use linux::measure_footprint;
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn measure_footprint(_run: impl FnOnce() + Send) -> Result<Footprint, String>
+{
+	return Err(String::from("--memory is only supported on Linux"));
+}
@@ -0,0 +1,622 @@
+// Command line options for the runner.
+
+use std::time::Duration;
+
+use crate::affinity::SchedulerPolicy;
+use crate::history::defaultHistoryPath;
+use crate::isolation::WorkerRequest;
+use crate::kernels::Kernel;
+use crate::ordering::Order;
+use crate::selection::check_patterns;
+use crate::sweep::parse_sweep;
+use crate::timer::TimerKind;
+
+pub(crate) struct Options
+{
+	pub(crate) warmups: usize,
+	pub(crate) samples: usize,
+	pub(crate) configPath: Option<String>,
+	pub(crate) include: Vec<String>,
+	pub(crate) exclude: Vec<String>,
+	pub(crate) cpu: Option<usize>,
+	pub(crate) nice: Option<i32>,
+	pub(crate) scheduler: Option<SchedulerPolicy>,
+	pub(crate) counters: bool,
+	pub(crate) allocations: bool,
+	pub(crate) memory: bool,
+	pub(crate) frequency: bool,
+	// Variation of the CPU frequency during a kernel, in percent, that gets a warning.
+	pub(crate) frequencyThreshold: f64,
+	// Run every kernel in a child process, killed after `timeout`.
+	pub(crate) isolate: bool,
+	pub(crate) timeout: Duration,
+	pub(crate) order: Order,
+	pub(crate) seed: Option<u64>,
+	pub(crate) timer: Option<TimerKind>,
+	// Target duration of one run in nanoseconds.
+	pub(crate) calibrate: Option<f64>,
+	// (parameter, values) for each --sweep, in the order given.
+	pub(crate) sweeps: Vec<(String, Vec<u32>)>,
+	pub(crate) sweepChartPath: Option<String>,
+	// "name=path" specs for --library.
+	pub(crate) libraries: Vec<String>,
+	// Implementation whose checksums the others must match.
+	pub(crate) reference: Option<String>,
+	pub(crate) validate: bool,
+	pub(crate) jsonPath: Option<String>,
+	pub(crate) csvPath: Option<String>,
+	pub(crate) baselinePath: Option<String>,
+	pub(crate) thresholdPercent: f64,
+	// JSON-lines file runs are appended to.
+	pub(crate) historyPath: String,
+	// Some when --history or --no-history decided whether to record the run. Otherwise only measurement
+	// runs are recorded, not calibration, sweeps or workloads with history = false.
+	pub(crate) recordHistory: Option<bool>
+}
+
+impl Default for Options
+{
+	// One warmup and one timed run, the same as Benchmarks.cs.
+	fn default() -> Self
+	{
+		Options
+		{
+			warmups: 1,
+			samples: 1,
+			configPath: None,
+			include: Vec::new(),
+			exclude: Vec::new(),
+			cpu: None,
+			nice: None,
+			scheduler: None,
+			counters: false,
+			allocations: false,
+			memory: false,
+			frequency: false,
+			frequencyThreshold: 10.0,
+			isolate: false,
+			timeout: Duration::from_secs(300),
+			order: Order::Sequential,
+			seed: None,
+			timer: None,
+			calibrate: None,
+			sweeps: Vec::new(),
+			sweepChartPath: None,
+			libraries: Vec::new(),
+			reference: None,
+			validate: true,
+			jsonPath: None,
+			csvPath: None,
+			baselinePath: None,
+			thresholdPercent: 5.0,
+			historyPath: String::from(defaultHistoryPath),
+			recordHistory: None
+		}
+	}
+}
+
+// Options for the commands that turn existing result files into something else instead of running
+// the benchmarks.
+pub(crate) struct ConvertOptions
+{
+	pub(crate) output: String,
+	pub(crate) inputPaths: Vec<String>
+}
+
+// "runner diff", which compares two result files kernel by kernel.
+pub(crate) struct DiffOptions
+{
+	pub(crate) oldPath: String,
+	pub(crate) newPath: String,
+	// Significant changes smaller than this are reported as within the threshold.
+	pub(crate) thresholdPercent: f64
+}
+
+// "runner history", which shows how kernels changed over the recorded runs.
+pub(crate) struct HistoryOptions
+{
+	pub(crate) historyPath: String,
+	pub(crate) include: Vec<String>,
+	pub(crate) exclude: Vec<String>,
+	pub(crate) implementation: Option<String>,
+	// How many points of each trend to show.
+	pub(crate) last: usize,
+	// Pool consecutive runs with the same rustc version into one point.
+	pub(crate) byToolchain: bool
+}
+
+// "runner worker", which measures one kernel for a parent runner with --isolate.
+pub(crate) struct WorkerOptions
+{
+	pub(crate) request: WorkerRequest,
+	pub(crate) options: Options
+}
+
+pub(crate) enum Command
+{
+	// Boxed because Options is much larger than the other variants.
+	Run(Box<Options>),
+	Worker(Box<WorkerOptions>),
+	History(HistoryOptions),
+	Diff(DiffOptions),
+	Chart(ConvertOptions),
+	Export(ConvertOptions),
+	Report(ConvertOptions)
+}
+
+pub(crate) const usage: &str = "\
+Usage: runner [options]
+       runner chart [--output <dir>] <result files...>
+       runner export [--output <path>] <result files...>
+       runner report [--output <dir>] <result files...>
+       runner diff [--threshold <percent>] <old result file> <new result file>
+       runner history [history options]
+
+Result files are JSON files written with --json, or Results.txt files written by
+this runner or by Benchmarks.cs.
+
+Options:
+  --warmups <n>    Untimed runs before sampling (default 1)
+  --samples <n>    Timed runs per kernel (default 1)
+  --config <path>  Workload file that enables or disables kernels and sets
+                   their parameters, e.g. workloads/smoke.toml (default: every
+                   kernel with the Benchmarks.cs parameters)
+  --kernel <pattern>
+                   Only run matching kernels. A pattern is a kernel name, a
+                   glob such as '*raytracer*', or a category (i32, f32, f64).
+                   Repeat the flag or separate patterns with commas
+  --exclude <pattern>
+                   Skip matching kernels, with the same patterns as --kernel
+  --cpu <n>        Pin the benchmark thread to one CPU and warn if its SMT
+                   siblings are busy (Linux only)
+  --nice <n>       Nice value from -20 to 19. Negative values need privileges
+  --scheduler <policy>
+                   Scheduling policy: other, batch, idle, fifo or rr. fifo and
+                   rr need privileges
+  --counters       Read performance counters around each timed run (cycles,
+                   instructions, IPC, branch and cache misses), or software
+                   counters where the hardware ones aren't available
+  --allocations    Count heap allocations, bytes allocated and peak heap use
+                   in each timed run of the Rust kernels
+  --memory         Run each kernel once more, untimed, and report its peak RSS
+                   and stack high-water mark (Linux only)
+  --frequency      Read the CPU frequency and temperature in the background
+                   during each timed run, and warn if the frequency varied,
+                   e.g. because the CPU throttled (Linux with cpufreq only)
+  --frequency-threshold <percent>
+                   Frequency variation that gets a warning. Implies
+                   --frequency (default 10)
+  --isolate        Run every kernel in its own child process, so that a crash,
+                   panic or hang is recorded as a failed entry and the other
+                   kernels still run. Failures add 4 to the exit status
+  --timeout <seconds>
+                   Kill a kernel's process after this long and record it as
+                   failed. Implies --isolate (default 300)
+  --timer <tsc|instant>
+                   Clock for the timed runs. The default is the TSC on x86-64
+                   CPUs with an invariant TSC, and Instant everywhere else
+  --calibrate <seconds>
+                   Scale each kernel's iteration-style parameter until one run
+                   takes about this long, and report the time per iteration
+  --sweep <parameter>=<values>
+                   Run the one selected kernel for every value of a parameter,
+                   e.g. boids=100,200,400, width=64..4096*2 (geometric) or
+                   iterations=100..1000:100 (linear), and report the time per
+                   unit of work at each point. Repeat for a grid
+  --sweep-chart <path>
+                   Also draw the sweep's time per unit of work as an SVG chart
+  --library [<name>=]<path>
+                   Also benchmark a shared object exporting the benchmark_*
+                   functions, e.g. GCC=./libbenchmarks-gcc.so. Can be repeated.
+                   A library named Rust replaces the kernels built into the
+                   runner
+  --reference <name>
+                   Check every implementation's checksums against this one,
+                   e.g. GCC, instead of only against the golden values recorded
+                   from the C library
+  --no-validate    Don't check checksums. Mismatches otherwise add 8 to the exit
+                   status, unless they are known divergences of the Rust ports
+  --order <sequential|interleaved|random>
+                   sequential takes all samples of a kernel before the next,
+                   like Benchmarks.cs. interleaved takes one sample of every
+                   kernel and implementation per round, and random also
+                   shuffles each round (default sequential)
+  --seed <n>       Seed for --order random. Without it a seed is picked and
+                   printed, so the order can be repeated
+  --json <path>    Also write the results as JSON
+  --csv <path>     Also write the results as CSV, one row per sample
+  --baseline <path>
+                   Compare against a saved JSON result file. Regressions add 1
+                   to the exit status
+  --threshold <percent>
+                   Slowdown that counts as a regression (default 5)
+  --history <path> Append the run, with its environment, to this JSON-lines
+                   file. Without it, runs are appended to
+                   benchmark_history.jsonl, except calibration, sweeps and
+                   workloads that set history = false
+  --no-history     Don't record the run
+  --help           Print this message
+
+Chart options:
+  --output <dir>   Directory for the SVG files (default img)
+
+Export options:
+  --output <path>  Spreadsheet in the layout of Benchmark Graph Template.ods.
+                   Written as ODS, or as CSV if the path ends in .csv
+                   (default \"Benchmark Results.ods\")
+
+Report options:
+  --output <dir>   Folder for README.md and img/ (default
+                   benchmark_results_<date of the run>)
+
+Diff options:
+  --threshold <percent>
+                   Statistically significant changes smaller than this are
+                   reported as within threshold (default 0). Changes the
+                   Mann-Whitney U test doesn't find significant are always
+                   marked as such
+
+History options:
+  --history <path> History file to read (default benchmark_history.jsonl)
+  --kernel <pattern>, --exclude <pattern>
+                   Only show matching kernels, as for a run. Exported symbols
+                   such as benchmark_pixar_raytracer also match
+  --implementation <name>
+                   Only show this implementation, e.g. Rust
+  --last <n>       Show the last n points of each kernel (default 20)
+  --by <run|toolchain>
+                   One point per run, or one per rustc version, pooling the
+                   samples of consecutive runs with the same version
+                   (default run)";
+
+fn parse_count(flag: &str, value: Option<String>) -> Result<usize, String>
+{
+	let value: String = value.ok_or_else(|| format!("{} expects a value", flag))?;
+
+	return value.parse::<usize>().map_err(|_| format!("{} expects a whole number, got '{}'", flag, value));
+}
+
+fn parse_percent(flag: &str, value: Option<String>) -> Result<f64, String>
+{
+	let value: String = value.ok_or_else(|| format!("{} expects a value", flag))?;
+	let percent: f64 = value.parse::<f64>().map_err(|_| format!("{} expects a number, got '{}'", flag, value))?;
+
+	if (percent.is_nan() || percent < 0.0)
+	{
+		return Err(format!("{} must be a non-negative number", flag));
+	}
+
+	return Ok(percent);
+}
+
+fn parse_nice(flag: &str, value: Option<String>) -> Result<i32, String>
+{
+	let value: String = value.ok_or_else(|| format!("{} expects a value", flag))?;
+
+	return match value.parse::<i32>()
+	{
+		Ok(nice) if (-20..=19).contains(&nice) => Ok(nice),
+		_ => Err(format!("{} expects a number from -20 to 19, got '{}'", flag, value))
+	};
+}
+
+fn parse_scheduler(flag: &str, value: Option<String>) -> Result<SchedulerPolicy, String>
+{
+	let value: String = value.ok_or_else(|| format!("{} expects a policy", flag))?;
+
+	return SchedulerPolicy::from_name(&value).ok_or_else(|| format!("{} expects other, batch, idle, fifo or rr, got '{}'", flag, value));
+}
+
+fn parse_timer(flag: &str, value: Option<String>) -> Result<TimerKind, String>
+{
+	let value: String = value.ok_or_else(|| format!("{} expects a timer", flag))?;
+
+	return TimerKind::from_name(&value).ok_or_else(|| format!("{} expects tsc or instant, got '{}'", flag, value));
+}
+
+fn parse_order(flag: &str, value: Option<String>) -> Result<Order, String>
+{
+	let value: String = value.ok_or_else(|| format!("{} expects an order", flag))?;
+
+	return Order::from_name(&value).ok_or_else(|| format!("{} expects sequential, interleaved or random, got '{}'", flag, value));
+}
+
+fn parse_seed(flag: &str, value: Option<String>) -> Result<u64, String>
+{
+	let value: String = value.ok_or_else(|| format!("{} expects a value", flag))?;
+
+	return value.parse::<u64>().map_err(|_| format!("{} expects a whole number, got '{}'", flag, value));
+}
+
+fn parse_patterns(flag: &str, value: Option<String>) -> Result<Vec<String>, String>
+{
+	let value: String = value.ok_or_else(|| format!("{} expects a pattern", flag))?;
+	let patterns: Vec<String> = value
+		.split(',')
+		.map(str::trim)
+		.filter(|pattern| !pattern.is_empty())
+		.map(String::from)
+		.collect();
+
+	check_patterns(&patterns)?;
+
+	return Ok(patterns);
+}
+
+fn parse_seconds(flag: &str, value: Option<String>) -> Result<f64, String>
+{
+	let value: String = value.ok_or_else(|| format!("{} expects a value", flag))?;
+
+	return match value.parse::<f64>()
+	{
+		Ok(seconds) if (seconds > 0.0 && seconds.is_finite()) => Ok(seconds * 1e9),
+		_ => Err(format!("{} expects a positive number of seconds, got '{}'", flag, value))
+	};
+}
+
+fn parse_name(flag: &str, value: Option<String>) -> Result<String, String>
+{
+	return value.ok_or_else(|| format!("{} expects an implementation name", flag));
+}
+
+fn parse_path(flag: &str, value: Option<String>) -> Result<String, String>
+{
+	return value.ok_or_else(|| format!("{} expects a path", flag));
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String>
+{
+	let mut options: Options = Options::default();
+
+	while let Some(arg) = args.next()
+	{
+		match arg.as_str()
+		{
+			"--warmups" => options.warmups = parse_count(&arg, args.next())?,
+			"--samples" => options.samples = parse_count(&arg, args.next())?,
+			"--config" => options.configPath = Some(parse_path(&arg, args.next())?),
+			"--kernel" => options.include.extend(parse_patterns(&arg, args.next())?),
+			"--exclude" => options.exclude.extend(parse_patterns(&arg, args.next())?),
+			"--cpu" => options.cpu = Some(parse_count(&arg, args.next())?),
+			"--nice" => options.nice = Some(parse_nice(&arg, args.next())?),
+			"--scheduler" => options.scheduler = Some(parse_scheduler(&arg, args.next())?),
+			"--counters" => options.counters = true,
+			"--allocations" => options.allocations = true,
+			"--memory" => options.memory = true,
+			"--frequency" => options.frequency = true,
+			"--frequency-threshold" =>
+			{
+				options.frequencyThreshold = parse_percent(&arg, args.next())?;
+				options.frequency = true;
+			}
+			"--isolate" => options.isolate = true,
+			"--order" => options.order = parse_order(&arg, args.next())?,
+			"--seed" => options.seed = Some(parse_seed(&arg, args.next())?),
+			"--timeout" =>
+			{
+				options.timeout = Duration::from_nanos(parse_seconds(&arg, args.next())? as u64);
+				options.isolate = true;
+			}
+			"--timer" => options.timer = Some(parse_timer(&arg, args.next())?),
+			"--calibrate" => options.calibrate = Some(parse_seconds(&arg, args.next())?),
+			"--sweep" => options.sweeps.push(parse_sweep(&args.next().ok_or_else(|| format!("{} expects <parameter>=<values>", arg))?)?),
+			"--sweep-chart" => options.sweepChartPath = Some(parse_path(&arg, args.next())?),
+			"--library" => options.libraries.push(parse_path(&arg, args.next())?),
+			"--reference" => options.reference = Some(parse_name(&arg, args.next())?),
+			"--no-validate" => options.validate = false,
+			"--json" => options.jsonPath = Some(parse_path(&arg, args.next())?),
+			"--csv" => options.csvPath = Some(parse_path(&arg, args.next())?),
+			"--baseline" => options.baselinePath = Some(parse_path(&arg, args.next())?),
+			"--threshold" => options.thresholdPercent = parse_percent(&arg, args.next())?,
+			"--history" =>
+			{
+				options.historyPath = parse_path(&arg, args.next())?;
+				options.recordHistory = Some(true);
+			}
+			"--no-history" => options.recordHistory = Some(false),
+			"--help" | "-h" =>
+			{
+				println!("{}", usage);
+				std::process::exit(0);
+			}
+			_ => return Err(format!("Unknown argument '{}'", arg))
+		}
+	}
+
+	if (options.samples == 0)
+	{
+		return Err(String::from("--samples must be at least 1"));
+	}
+
+	if (options.seed.is_some() && options.order != Order::Random)
+	{
+		return Err(String::from("--seed only applies to --order random"));
+	}
+
+	if (options.isolate && options.order != Order::Sequential)
+	{
+		return Err(String::from("--isolate runs every kernel in one process, so it needs --order sequential"));
+	}
+
+	if (!options.sweeps.is_empty() && options.calibrate.is_some())
+	{
+		return Err(String::from("--calibrate changes the parameters that --sweep sets, so they can't be used together"));
+	}
+
+	if (options.sweepChartPath.is_some() && options.sweeps.is_empty())
+	{
+		return Err(String::from("--sweep-chart needs --sweep"));
+	}
+
+	if (options.reference.is_some() && !options.validate)
+	{
+		return Err(String::from("--reference and --no-validate can't be used together"));
+	}
+
+	return Ok(options);
+}
+
+fn parse_worker_options(mut args: impl Iterator<Item = String>) -> Result<WorkerOptions, String>
+{
+	let mut kernel: Option<Kernel> = None;
+	let mut parameters: Vec<u32> = Vec::new();
+	let mut implementation: String = String::new();
+	let mut tscFrequency: Option<f64> = None;
+
+	while let Some(arg) = args.next()
+	{
+		let value: Option<String> = if (arg == "--") { None } else { args.next() };
+
+		match (arg.as_str(), value)
+		{
+			("--", _) => break,
+			("--kernel-name", Some(name)) => kernel = Kernel::from_name(&name),
+			("--parameters", Some(list)) => parameters = list.split(',').filter_map(|value| value.parse::<u32>().ok()).collect(),
+			("--implementation", Some(name)) => implementation = name,
+			("--tsc-frequency", Some(frequency)) => tscFrequency = frequency.parse::<f64>().ok(),
+			_ => return Err(format!("Invalid worker argument '{}'", arg))
+		}
+	}
+
+	let kernel: Kernel = kernel.ok_or_else(|| String::from("The worker needs a valid --kernel-name"))?;
+	if (parameters.len() != kernel.parameter_names().len())
+	{
+		return Err(format!("The worker needs {} parameters for {}", kernel.parameter_names().len(), kernel.name()));
+	}
+
+	return Ok(WorkerOptions
+	{
+		request: WorkerRequest { kernel, parameters, implementation, tscFrequency },
+		options: parse_options(args)?
+	});
+}
+
+fn parse_diff_options(mut args: impl Iterator<Item = String>) -> Result<DiffOptions, String>
+{
+	let mut thresholdPercent: f64 = 0.0;
+	let mut paths: Vec<String> = Vec::new();
+
+	while let Some(arg) = args.next()
+	{
+		match arg.as_str()
+		{
+			"--threshold" => thresholdPercent = parse_percent(&arg, args.next())?,
+			_ if arg.starts_with("--") => return Err(format!("Unknown argument '{}'", arg)),
+			_ => paths.push(arg)
+		}
+	}
+
+	let [oldPath, newPath]: [String; 2] = paths.try_into().map_err(|_| String::from("diff expects exactly two result files, the old one first"))?;
+
+	return Ok(DiffOptions { oldPath, newPath, thresholdPercent });
+}
+
+fn parse_history_options(mut args: impl Iterator<Item = String>) -> Result<HistoryOptions, String>
+{
+	let mut options: HistoryOptions = HistoryOptions
+	{
+		historyPath: String::from(defaultHistoryPath),
+		include: Vec::new(),
+		exclude: Vec::new(),
+		implementation: None,
+		last: 20,
+		byToolchain: false
+	};
+
+	while let Some(arg) = args.next()
+	{
+		match arg.as_str()
+		{
+			"--history" => options.historyPath = parse_path(&arg, args.next())?,
+			"--kernel" => options.include.extend(parse_patterns(&arg, args.next())?),
+			"--exclude" => options.exclude.extend(parse_patterns(&arg, args.next())?),
+			"--implementation" => options.implementation = Some(parse_name(&arg, args.next())?),
+			"--last" => options.last = parse_count(&arg, args.next())?,
+			"--by" =>
+			{
+				options.byToolchain = match args.next().as_deref()
+				{
+					Some("run") => false,
+					Some("toolchain") => true,
+					Some(other) => return Err(format!("--by expects run or toolchain, got '{}'", other)),
+					None => return Err(String::from("--by expects run or toolchain"))
+				};
+			}
+			_ => return Err(format!("Unknown argument '{}'", arg))
+		}
+	}
+
+	if (options.last == 0)
+	{
+		return Err(String::from("--last must be at least 1"));
+	}
+
+	return Ok(options);
+}
+
+fn parse_convert_options(command: &str, defaultOutput: &str, mut args: impl Iterator<Item = String>) -> Result<ConvertOptions, String>
+{
+	let mut options: ConvertOptions = ConvertOptions
+	{
+		output: String::from(defaultOutput),
+		inputPaths: Vec::new()
+	};
+
+	while let Some(arg) = args.next()
+	{
+		match arg.as_str()
+		{
+			"--output" => options.output = parse_path(&arg, args.next())?,
+			_ if arg.starts_with("--") => return Err(format!("Unknown argument '{}'", arg)),
+			_ => options.inputPaths.push(arg)
+		}
+	}
+
+	if (options.inputPaths.is_empty())
+	{
+		return Err(format!("{} expects at least one result file", command));
+	}
+
+	return Ok(options);
+}
+
+pub(crate) fn parse_command(args: impl Iterator<Item = String>) -> Result<Command, String>
+{
+	let mut args = args.peekable();
+
+	return match args.peek().map(String::as_str)
+	{
+		Some("chart") =>
+		{
+			args.next();
+			parse_convert_options("chart", "img", args).map(Command::Chart)
+		}
+		Some("export") =>
+		{
+			args.next();
+			parse_convert_options("export", "Benchmark Results.ods", args).map(Command::Export)
+		}
+		Some("worker") =>
+		{
+			args.next();
+			parse_worker_options(args).map(|worker| Command::Worker(Box::new(worker)))
+		}
+		Some("diff") =>
+		{
+			args.next();
+			parse_diff_options(args).map(Command::Diff)
+		}
+		Some("history") =>
+		{
+			args.next();
+			parse_history_options(args).map(Command::History)
+		}
+		Some("report") =>
+		{
+			args.next();
+			// An empty output is replaced with a name based on the date of the results.
+			parse_convert_options("report", "", args).map(Command::Report)
+		}
+		_ => parse_options(args).map(|options| Command::Run(Box::new(options)))
+	};
+}
@@ -0,0 +1,55 @@
+// The order in which timed samples are taken. Benchmarks.cs finishes one kernel before starting the
+// next, so kernels late in the list always run on a warmer CPU. Interleaving takes one sample of every
+// kernel and implementation per round, and the random order shuffles each round as well, so that
+// thermal drift is spread over all of them instead of landing on the last few.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::statistics::XorShift;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Order
+{
+	Sequential,
+	Interleaved,
+	Random
+}
+
+impl Order
+{
+	pub(crate) fn from_name(name: &str) -> Option<Order>
+	{
+		return match name
+		{
+			"sequential" => Some(Order::Sequential),
+			"interleaved" => Some(Order::Interleaved),
+			"random" => Some(Order::Random),
+			_ => None
+		};
+	}
+
+	pub(crate) fn as_str(self) -> &'static str
+	{
+		return match self
+		{
+			Order::Sequential => "sequential",
+			Order::Interleaved => "interleaved",
+			Order::Random => "random"
+		};
+	}
+}
+
+// A seed for --order random without --seed. It is printed and recorded, so the run can be repeated.
+pub(crate) fn default_seed() -> u64
+{
+	return SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_nanos() as u64).unwrap_or(0);
+}
+
+// Fisher-Yates.
+pub(crate) fn shuffle<T>(items: &mut [T], random: &mut XorShift)
+{
+	for i in (1..items.len()).rev()
+	{
+		items.swap(i, random.below(i + 1));
+	}
+}
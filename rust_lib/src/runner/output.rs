@@ -0,0 +1,590 @@
+// Machine-readable result files. Bump schemaVersion whenever a field is renamed, removed or changes
+// meaning; adding a field does not need a bump.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::allocations::AllocationSample;
+use crate::counters::Counter;
+use crate::environment::{environment_to_json, Environment};
+use crate::frequency::{frequency_variation, FrequencySample};
+use crate::isolation::{failure_to_json, Failure};
+use crate::json::{object, parse_json, string, JsonValue};
+use crate::kernels::{Checksum, Kernel};
+use crate::measurement::Measurement;
+use crate::memory::Footprint;
+use crate::statistics::{compute_statistics, median, Statistics};
+use crate::validation::Validation;
+
+pub(crate) const schemaName: &str = "burst-benchmarks-results";
+pub(crate) const schemaVersion: u32 = 1;
+pub(crate) const timeUnit: &str = "ns";
+
+const csvHeader: &str = "schema_version,implementation,kernel,symbol,category,parameters,checksum_type,checksum,sample,time,unit,position";
+
+fn statistics_to_json(statistics: &Statistics) -> JsonValue
+{
+	return object(vec![
+		("samples", JsonValue::Number(statistics.samples as f64)),
+		("outliers", JsonValue::Number(statistics.outliers as f64)),
+		("min", JsonValue::Number(statistics.min)),
+		("median", JsonValue::Number(statistics.median)),
+		("mean", JsonValue::Number(statistics.mean)),
+		("standard_deviation", JsonValue::Number(statistics.standardDeviation)),
+		("median_absolute_deviation", JsonValue::Number(statistics.medianAbsoluteDeviation)),
+		("confidence_low", JsonValue::Number(statistics.confidenceLow)),
+		("confidence_high", JsonValue::Number(statistics.confidenceHigh))
+	]);
+}
+
+fn parameters_to_json(measurement: &Measurement) -> JsonValue
+{
+	return JsonValue::Object(
+		measurement.kernel.parameter_names()
+			.iter()
+			.zip(measurement.parameters.iter())
+			.map(|(name, value)| (String::from(*name), JsonValue::Number(*value as f64)))
+			.collect()
+	);
+}
+
+fn numbers(values: &[f64]) -> JsonValue
+{
+	return JsonValue::Array(values.iter().map(|value| JsonValue::Number(*value)).collect());
+}
+
+// Per-sample counter values, in the same order as "samples".
+fn counters_to_json(measurement: &Measurement) -> JsonValue
+{
+	let mut counters: Vec<(String, JsonValue)> = measurement.counters
+		.iter()
+		.map(|(counter, values)| (String::from(counter.name()), numbers(values)))
+		.collect();
+
+	if let Some(ipc) = measurement.instructions_per_cycle()
+	{
+		counters.push((String::from("ipc"), numbers(&ipc)));
+	}
+
+	return JsonValue::Object(counters);
+}
+
+// {"status": "match" | "mismatch" | "known_divergence" | "unchecked", "reference": ..., "expected": ...}.
+// The reference is another implementation's name or "golden".
+fn validation_to_json(validation: &Validation) -> JsonValue
+{
+	let mut fields: Vec<(&str, JsonValue)> = vec![("status", string(validation.status()))];
+
+	match validation
+	{
+		Validation::Unchecked => {}
+		Validation::Match { reference } => fields.push(("reference", string(reference))),
+		Validation::Mismatch { reference, expected } =>
+		{
+			fields.push(("reference", string(reference)));
+			fields.push(("expected", JsonValue::String(expected.to_string())));
+		}
+		Validation::KnownDivergence { reference, expected, reason } =>
+		{
+			fields.push(("reference", string(reference)));
+			fields.push(("expected", JsonValue::String(expected.to_string())));
+			fields.push(("reason", string(reason)));
+		}
+	}
+
+	return object(fields);
+}
+
+// Per-sample values, in the same order as "samples".
+fn allocations_to_json(measurement: &Measurement) -> JsonValue
+{
+	let values = |field: fn(&AllocationSample) -> u64| numbers(&measurement.allocations.iter().map(|sample| field(sample) as f64).collect::<Vec<f64>>());
+
+	return object(vec![
+		("count", values(|sample| sample.count)),
+		("bytes", values(|sample| sample.bytes)),
+		("peak_bytes", values(|sample| sample.peakBytes))
+	]);
+}
+
+// Per-sample values, in the same order as "samples", and the variation over all of them.
+fn frequencies_to_json(measurement: &Measurement) -> JsonValue
+{
+	let values = |field: fn(&FrequencySample) -> f64| numbers(&measurement.frequencies.iter().map(field).collect::<Vec<f64>>());
+	let mut fields: Vec<(&str, JsonValue)> = vec![
+		("cpu", values(|sample| sample.cpu as f64)),
+		("min_mhz", values(|sample| sample.minimum)),
+		("max_mhz", values(|sample| sample.maximum)),
+		("average_mhz", values(|sample| sample.average))
+	];
+
+	if (measurement.frequencies.iter().any(|sample| sample.temperature.is_some()))
+	{
+		fields.push(("temperature_c", values(|sample| sample.temperature.unwrap_or(f64::NAN))));
+	}
+
+	if let Some(variation) = frequency_variation(&measurement.frequencies)
+	{
+		fields.push(("variation_percent", JsonValue::Number(variation)));
+	}
+
+	return object(fields);
+}
+
+fn footprint_to_json(footprint: &Footprint) -> JsonValue
+{
+	return object(vec![
+		("peak_rss_bytes", JsonValue::Number(footprint.peakRss as f64)),
+		("rss_growth_bytes", JsonValue::Number(footprint.rssGrowth as f64)),
+		("stack_bytes", JsonValue::Number(footprint.stackBytes as f64))
+	]);
+}
+
+pub(crate) fn measurement_to_json(measurement: &Measurement) -> JsonValue
+{
+	// Checksums are written as strings because u64 hashes don't survive a trip through a double.
+	let mut fields: Vec<(&str, JsonValue)> = vec![
+		("implementation", string(&measurement.implementation)),
+		("kernel", string(measurement.kernel.name())),
+		("symbol", string(measurement.kernel.symbol())),
+		("category", string(measurement.kernel.category().as_str())),
+		("parameters", parameters_to_json(measurement)),
+		("checksum_type", string(measurement.checksum.type_name())),
+		("checksum", JsonValue::String(measurement.checksum.to_string())),
+		("unit", string(timeUnit)),
+		("samples", numbers(&measurement.samples)),
+		("positions", JsonValue::Array(measurement.positions.iter().map(|position| JsonValue::Number(*position as f64)).collect())),
+		("statistics", statistics_to_json(&measurement.statistics)),
+		("validation", validation_to_json(&measurement.validation))
+	];
+
+	if let Some((time, unit)) = measurement.time_per_unit()
+	{
+		fields.push(("time_per_unit", JsonValue::Number(time)));
+		fields.push(("work_unit", string(unit)));
+	}
+
+	if let Some((time, unit)) = measurement.time_per_work()
+	{
+		fields.push(("time_per_work", JsonValue::Number(time)));
+		fields.push(("work_name", string(unit)));
+	}
+
+	if (!measurement.cycles.is_empty())
+	{
+		fields.push(("tsc_cycles", numbers(&measurement.cycles)));
+	}
+
+	if (!measurement.counters.is_empty())
+	{
+		fields.push(("counters", counters_to_json(measurement)));
+	}
+
+	if (!measurement.allocations.is_empty())
+	{
+		fields.push(("allocations", allocations_to_json(measurement)));
+	}
+
+	if (!measurement.frequencies.is_empty())
+	{
+		fields.push(("frequency", frequencies_to_json(measurement)));
+	}
+
+	match &measurement.footprint
+	{
+		Some(Ok(footprint)) => fields.push(("memory", footprint_to_json(footprint))),
+		Some(Err(message)) => fields.push(("memory_error", string(message))),
+		None => {}
+	}
+
+	return object(fields);
+}
+
+fn numbers_from_json(value: Option<&JsonValue>) -> Vec<f64>
+{
+	return value
+		.and_then(JsonValue::as_array)
+		.map(|values| values.iter().map(|value| value.as_f64().unwrap_or(f64::NAN)).collect())
+		.unwrap_or_default();
+}
+
+// The reverse of measurement_to_json, for measurements made by a worker process. Statistics are
+// recomputed from the samples and validation is left to the caller.
+pub(crate) fn measurement_from_json(value: &JsonValue) -> Result<Measurement, String>
+{
+	let kernelName: String = field_str(value, "kernel")?;
+	let kernel: Kernel = Kernel::from_name(&kernelName).ok_or_else(|| format!("Unknown kernel '{}'", kernelName))?;
+	let parameters: Vec<u32> = kernel.parameter_names()
+		.iter()
+		.map(|name| value.get("parameters").and_then(|parameters| parameters.get(name)).and_then(JsonValue::as_f64).map(|value| value as u32))
+		.collect::<Option<Vec<u32>>>()
+		.ok_or_else(|| String::from("Missing or invalid 'parameters'"))?;
+	let checksum: Checksum = Checksum::parse(&field_str(value, "checksum_type")?, &field_str(value, "checksum")?)
+		.ok_or_else(|| String::from("Invalid 'checksum'"))?;
+	let samples: Vec<f64> = numbers_from_json(value.get("samples"));
+
+	let counters: Vec<(Counter, Vec<f64>)> = value.get("counters")
+		.and_then(JsonValue::as_object)
+		.unwrap_or_default()
+		.iter()
+		.filter_map(|(name, values)| Some((Counter::from_name(name)?, numbers_from_json(Some(values)))))
+		.collect();
+
+	let allocations: Vec<AllocationSample> = match value.get("allocations")
+	{
+		Some(allocations) =>
+		{
+			let values = |key: &str| numbers_from_json(allocations.get(key));
+			let (counts, bytes, peaks) = (values("count"), values("bytes"), values("peak_bytes"));
+
+			counts.iter()
+				.zip(bytes.iter().zip(peaks.iter()))
+				.map(|(count, (bytes, peak))| AllocationSample { count: *count as u64, bytes: *bytes as u64, peakBytes: *peak as u64 })
+				.collect()
+		}
+		None => Vec::new()
+	};
+
+	let frequencies: Vec<FrequencySample> = match value.get("frequency")
+	{
+		Some(frequency) =>
+		{
+			let values = |key: &str| numbers_from_json(frequency.get(key));
+			let (cpus, minimums, maximums, averages) = (values("cpu"), values("min_mhz"), values("max_mhz"), values("average_mhz"));
+			let temperatures: Vec<f64> = values("temperature_c");
+
+			(0..cpus.len().min(minimums.len()).min(maximums.len()).min(averages.len()))
+				.map(|index| FrequencySample
+				{
+					cpu: cpus[index] as usize,
+					minimum: minimums[index],
+					maximum: maximums[index],
+					average: averages[index],
+					temperature: temperatures.get(index).copied().filter(|temperature| !temperature.is_nan())
+				})
+				.collect()
+		}
+		None => Vec::new()
+	};
+
+	let footprint: Option<Result<Footprint, String>> = match (value.get("memory"), value.get("memory_error").and_then(JsonValue::as_str))
+	{
+		(Some(memory), _) =>
+		{
+			let field = |key: &str| memory.get(key).and_then(JsonValue::as_f64).unwrap_or(0.0) as u64;
+			Some(Ok(Footprint { peakRss: field("peak_rss_bytes"), rssGrowth: field("rss_growth_bytes"), stackBytes: field("stack_bytes") }))
+		}
+		(None, Some(message)) => Some(Err(String::from(message))),
+		(None, None) => None
+	};
+
+	return Ok(Measurement
+	{
+		implementation: field_str(value, "implementation")?,
+		kernel,
+		parameters,
+		checksum,
+		statistics: compute_statistics(&samples),
+		samples,
+		cycles: numbers_from_json(value.get("tsc_cycles")),
+		counters,
+		allocations,
+		frequencies,
+		positions: numbers_from_json(value.get("positions")).iter().map(|position| *position as u64).collect(),
+		footprint,
+		validation: Validation::Unchecked
+	});
+}
+
+// Kernels that crashed or timed out with --isolate are listed under "failures" rather than "results".
+pub(crate) fn results_to_json(environment: &Environment, measurements: &[Measurement], failures: &[Failure]) -> JsonValue
+{
+	return object(vec![
+		("schema", string(schemaName)),
+		("schema_version", JsonValue::Number(schemaVersion as f64)),
+		("environment", environment_to_json(environment)),
+		("results", JsonValue::Array(measurements.iter().map(measurement_to_json).collect())),
+		("failures", JsonValue::Array(failures.iter().map(failure_to_json).collect()))
+	]);
+}
+
+pub(crate) fn write_json(path: &str, environment: &Environment, measurements: &[Measurement], failures: &[Failure]) -> io::Result<()>
+{
+	return fs::write(path, results_to_json(environment, measurements, failures).to_pretty_string());
+}
+
+pub(crate) fn csv_field(value: &str) -> String
+{
+	if (value.contains(',') || value.contains('"') || value.contains('\n'))
+	{
+		return format!("\"{}\"", value.replace('"', "\"\""));
+	}
+
+	return String::from(value);
+}
+
+// Parameters share a single column as "name=value" pairs separated by semicolons.
+fn parameters_to_csv(measurement: &Measurement) -> String
+{
+	return measurement.kernel.parameter_names()
+		.iter()
+		.zip(measurement.parameters.iter())
+		.map(|(name, value)| format!("{}={}", name, value))
+		.collect::<Vec<String>>()
+		.join(";");
+}
+
+// One row per timing sample. CSV has nowhere to put the environment, so it goes into a
+// "<name>.environment.json" file next to it.
+pub(crate) fn write_csv(path: &str, environment: &Environment, measurements: &[Measurement]) -> io::Result<()>
+{
+	fs::write(Path::new(path).with_extension("environment.json"), environment_to_json(environment).to_pretty_string())?;
+
+	let mut output: String = String::from(csvHeader);
+	output.push('\n');
+
+	for measurement in measurements
+	{
+		for (index, sample) in measurement.samples.iter().enumerate()
+		{
+			let row: [String; 12] = [
+				schemaVersion.to_string(),
+				csv_field(&measurement.implementation),
+				csv_field(measurement.kernel.name()),
+				String::from(measurement.kernel.symbol()),
+				String::from(measurement.kernel.category().as_str()),
+				parameters_to_csv(measurement),
+				String::from(measurement.checksum.type_name()),
+				measurement.checksum.to_string(),
+				index.to_string(),
+				sample.to_string(),
+				String::from(timeUnit),
+				measurement.positions.get(index).map(u64::to_string).unwrap_or_default()
+			];
+
+			output.push_str(&row.join(","));
+			output.push('\n');
+		}
+	}
+
+	return fs::write(path, output);
+}
+
+// One kernel's results as read back from a JSON file. Kernels are kept by name rather than as a
+// Kernel so that files from other implementations and older runners still load.
+pub(crate) struct ResultRecord
+{
+	pub(crate) implementation: String,
+	pub(crate) kernel: String,
+	pub(crate) symbol: String,
+	pub(crate) parameters: Vec<(String, u32)>,
+	pub(crate) checksumType: String,
+	pub(crate) checksum: String,
+	pub(crate) unit: String,
+	pub(crate) samples: Vec<f64>,
+	// (expected checksum, reference) if the checksum didn't match.
+	pub(crate) mismatch: Option<(String, String)>
+}
+
+pub(crate) struct ResultFile
+{
+	pub(crate) environment: Option<JsonValue>,
+	pub(crate) records: Vec<ResultRecord>,
+	// Anything odd found while reading the file, for the caller to print as warnings.
+	pub(crate) warnings: Vec<String>
+}
+
+impl ResultRecord
+{
+	pub(crate) fn from_measurement(measurement: &Measurement) -> Self
+	{
+		ResultRecord
+		{
+			implementation: measurement.implementation.clone(),
+			kernel: String::from(measurement.kernel.name()),
+			symbol: String::from(measurement.kernel.symbol()),
+			parameters: measurement.kernel.parameter_names()
+				.iter()
+				.zip(measurement.parameters.iter())
+				.map(|(name, value)| (String::from(*name), *value))
+				.collect(),
+			checksumType: String::from(measurement.checksum.type_name()),
+			checksum: measurement.checksum.to_string(),
+			unit: String::from(timeUnit),
+			samples: measurement.samples.clone(),
+			mismatch: match &measurement.validation
+			{
+				Validation::Mismatch { reference, expected } => Some((expected.to_string(), reference.clone())),
+				_ => None
+			}
+		}
+	}
+
+	pub(crate) fn median(&self) -> f64
+	{
+		let mut sorted: Vec<f64> = self.samples.clone();
+		sorted.sort_by(f64::total_cmp);
+
+		return median(&sorted);
+	}
+
+	// Two records describe the same experiment when they ran the same symbol of the same
+	// implementation with the same inputs.
+	pub(crate) fn same_workload(&self, other: &ResultRecord) -> bool
+	{
+		return self.implementation == other.implementation && self.symbol == other.symbol && self.parameters == other.parameters;
+	}
+}
+
+fn field_str(value: &JsonValue, key: &str) -> Result<String, String>
+{
+	return value.get(key)
+		.and_then(JsonValue::as_str)
+		.map(String::from)
+		.ok_or_else(|| format!("Missing or invalid '{}'", key));
+}
+
+fn record_from_json(value: &JsonValue) -> Result<ResultRecord, String>
+{
+	let parameters: Vec<(String, u32)> = value.get("parameters")
+		.and_then(JsonValue::as_object)
+		.ok_or_else(|| String::from("Missing or invalid 'parameters'"))?
+		.iter()
+		.map(|(name, value)| (name.clone(), value.as_f64().unwrap_or(0.0) as u32))
+		.collect();
+	let samples: Vec<f64> = value.get("samples")
+		.and_then(JsonValue::as_array)
+		.ok_or_else(|| String::from("Missing or invalid 'samples'"))?
+		.iter()
+		.filter_map(JsonValue::as_f64)
+		.collect();
+	// Older files have no "validation".
+	let mismatch: Option<(String, String)> = value.get("validation")
+		.filter(|validation| validation.get("status").and_then(JsonValue::as_str) == Some("mismatch"))
+		.map(|validation| (field_str(validation, "expected").unwrap_or_default(), field_str(validation, "reference").unwrap_or_default()));
+
+	return Ok(ResultRecord
+	{
+		implementation: field_str(value, "implementation")?,
+		kernel: field_str(value, "kernel")?,
+		symbol: field_str(value, "symbol")?,
+		parameters,
+		checksumType: field_str(value, "checksum_type")?,
+		checksum: field_str(value, "checksum")?,
+		unit: field_str(value, "unit")?,
+		samples,
+		mismatch
+	});
+}
+
+pub(crate) fn parse_results(text: &str) -> Result<ResultFile, String>
+{
+	let document: JsonValue = parse_json(text)?;
+
+	if (document.get("schema").and_then(JsonValue::as_str) != Some(schemaName))
+	{
+		return Err(format!("Not a {} file", schemaName));
+	}
+
+	let version: f64 = document.get("schema_version").and_then(JsonValue::as_f64).unwrap_or(0.0);
+	if (version < 1.0 || version > schemaVersion as f64)
+	{
+		return Err(format!("Unsupported schema version {}", version));
+	}
+
+	let records: Vec<ResultRecord> = document.get("results")
+		.and_then(JsonValue::as_array)
+		.ok_or_else(|| String::from("Missing or invalid 'results'"))?
+		.iter()
+		.map(record_from_json)
+		.collect::<Result<Vec<ResultRecord>, String>>()?;
+
+	return Ok(ResultFile
+	{
+		environment: document.get("environment").cloned(),
+		records,
+		warnings: Vec::new()
+	});
+}
+
+pub(crate) fn read_json(path: &str) -> Result<ResultFile, String>
+{
+	let text: String = fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+
+	return parse_results(&text).map_err(|error| format!("{}: {}", path, error));
+}
+
+// Reads a Results.txt file written by the runner or by Benchmarks.cs, i.e. lines such as
+// "(GCC) Fibonacci: 123 ticks. Result: 2971215073". Every run appended to the file becomes one sample.
+// The file has no parameters, so a changed result is taken to mean a changed workload: the kernel's
+// earlier runs are dropped with a warning rather than pooled with unrelated timings.
+pub(crate) fn parse_results_text(text: &str) -> ResultFile
+{
+	let mut records: Vec<ResultRecord> = Vec::new();
+	let mut warnings: Vec<String> = Vec::new();
+
+	for (index, line) in text.lines().enumerate()
+	{
+		let Some(rest) = line.trim().strip_prefix('(') else { continue };
+		let Some((implementation, rest)) = rest.split_once(") ") else { continue };
+		let Some((kernel, rest)) = rest.split_once(": ") else { continue };
+		let Some((ticks, checksum)) = rest.split_once(" ticks. Result: ") else { continue };
+		let Ok(ticks) = ticks.parse::<f64>() else { continue };
+
+		let existing: Option<&mut ResultRecord> = records
+			.iter_mut()
+			.find(|record| record.implementation == implementation && record.kernel == kernel);
+
+		match existing
+		{
+			Some(record) if (record.checksum != checksum) =>
+			{
+				warnings.push(format!(
+					"({}) {}: the result changed from {} to {} on line {}, so the {} run(s) before it are left out as a different workload",
+					implementation,
+					kernel,
+					record.checksum,
+					checksum,
+					index + 1,
+					record.samples.len()
+				));
+				record.checksum = String::from(checksum);
+				record.samples = vec![ticks];
+			}
+			Some(record) => record.samples.push(ticks),
+			None => records.push(ResultRecord
+			{
+				implementation: String::from(implementation),
+				kernel: String::from(kernel),
+				symbol: String::from(Kernel::from_name(kernel).map(Kernel::symbol).unwrap_or_default()),
+				parameters: Vec::new(),
+				checksumType: String::new(),
+				checksum: String::from(checksum),
+				unit: String::from("ticks"),
+				samples: vec![ticks],
+				mismatch: None
+			})
+		}
+	}
+
+	return ResultFile
+	{
+		environment: None,
+		records,
+		warnings
+	};
+}
+
+// JSON files are recognised by their extension, anything else is read as Results.txt.
+pub(crate) fn read_results(path: &str) -> Result<ResultFile, String>
+{
+	if (path.ends_with(".json"))
+	{
+		return read_json(path);
+	}
+
+	let text: String = fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+
+	return Ok(parse_results_text(&text));
+}
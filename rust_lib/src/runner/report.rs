@@ -0,0 +1,204 @@
+// Builds a benchmark_results_<date> folder: a README in the style of the published ones and the chart
+// images it shows.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::chart::{chart_name, implementation_order, kernel_order, runtime_name, write_charts};
+use crate::environment::date_from_timestamp;
+use crate::json::JsonValue;
+use crate::kernels::{Category, Kernel};
+use crate::output::ResultRecord;
+
+const monthNames: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+// Legend descriptions for the runtimes that appear in the published results.
+fn describe_implementation(implementation: &str) -> Option<&'static str>
+{
+	return match runtime_name(implementation).as_str()
+	{
+		"Rust" => Some("The Rust benchmarks compiled with rustc and executed by the native Rust runner."),
+		"GCC" => Some("The C Library benchmarks compiled with the GCC compiler."),
+		"Clang" => Some("The C Library benchmarks compiled with the Clang compiler."),
+		"MS" => Some("The C++ Library benchmarks compiled with the Microsoft Visual C++ compiler."),
+		"Burst" => Some("The C# benchmarks compiled with the Burst compiler and executed with the Unity runtime."),
+		"GCC – Unity" => Some("The C Library benchmarks compiled with the GCC compiler and executed with the Unity runtime."),
+		"Clang – Unity" => Some("The C Library benchmarks compiled with the Clang compiler and executed with the Unity runtime."),
+		"IL2CPP" => Some("The C# benchmarks executed with the Unity runtime with the scripting backend set to \"IL2CPP\"."),
+		"Mono JIT" => Some("The C# benchmarks executed with the Unity runtime with the scripting backend set to \"Mono\"."),
+		"Ryu JIT" => Some("The C# benchmarks executed using a C# application through the .NET Runtime."),
+		_ => None
+	};
+}
+
+// e.g. "20 Oct 2022", the format used in the published README titles.
+pub(crate) fn format_long_date(timestamp: u64) -> String
+{
+	let (year, month, day) = date_from_timestamp(timestamp);
+
+	return format!("{} {} {}", day, monthNames[month as usize - 1], year);
+}
+
+// e.g. "2022-10-20", the format used in the folder names.
+pub(crate) fn format_iso_date(timestamp: u64) -> String
+{
+	let (year, month, day) = date_from_timestamp(timestamp);
+
+	return format!("{:04}-{:02}-{:02}", year, month, day);
+}
+
+fn environment_field<'a>(environment: &'a JsonValue, key: &str) -> &'a str
+{
+	return environment.get(key).and_then(JsonValue::as_str).unwrap_or("unknown");
+}
+
+fn write_environment_section(markdown: &mut String, environment: Option<&JsonValue>)
+{
+	markdown.push_str("## Software versions and environment\n");
+
+	let Some(environment) = environment else
+	{
+		markdown.push_str("No environment metadata was recorded with these results.\n\n");
+		return;
+	};
+
+	let features: Vec<&str> = environment.get("target_features")
+		.and_then(JsonValue::as_array)
+		.map(|features| features.iter().filter_map(JsonValue::as_str).collect())
+		.unwrap_or_default();
+	let rustflags: &str = environment_field(environment, "rustflags");
+
+	writeln!(markdown, "{}<br/>\n", environment_field(environment, "rustc_version")).unwrap();
+	write!(
+		markdown,
+		"Rust code compiled using the `{}` profile (opt-level {}) for `{}`",
+		environment_field(environment, "profile"),
+		environment_field(environment, "opt_level"),
+		environment_field(environment, "target")
+	).unwrap();
+	if (!rustflags.is_empty())
+	{
+		write!(markdown, " with `RUSTFLAGS=\"{}\"`", rustflags).unwrap();
+	}
+	writeln!(markdown, ". Enabled target features: {}.", features.join(", ")).unwrap();
+	// Libraries loaded with --library get a paragraph of their own, one per line, e.g.
+	// "GCC: `/path/libbenchmarks-gcc.so`, compiled with GCC: (Debian 12.2.0-14) 12.2.0.".
+	let libraries: &[JsonValue] = environment.get("libraries").and_then(JsonValue::as_array).unwrap_or_default();
+	if (!libraries.is_empty())
+	{
+		markdown.push('\n');
+	}
+	for library in libraries
+	{
+		writeln!(
+			markdown,
+			"{}: `{}`, compiled with {}.<br/>",
+			environment_field(library, "name"),
+			environment_field(library, "path"),
+			environment_field(library, "compiler")
+		).unwrap();
+	}
+	writeln!(
+		markdown,
+		"Benchmarks were done on Linux {} w/ {} (frequency governor: {}, SMT: {}, CPU affinity: {}).\n",
+		environment_field(environment, "kernel_version"),
+		environment_field(environment, "cpu_model"),
+		environment_field(environment, "governor"),
+		environment_field(environment, "smt_control"),
+		environment_field(environment, "cpu_affinity")
+	).unwrap();
+}
+
+// Kernels that computed something other than the reference, which makes their bars meaningless.
+fn write_mismatches(markdown: &mut String, records: &[ResultRecord])
+{
+	let mismatched: Vec<&ResultRecord> = records.iter().filter(|record| record.mismatch.is_some()).collect();
+
+	if (mismatched.is_empty())
+	{
+		return;
+	}
+
+	writeln!(markdown, "## Checksum mismatches\n").unwrap();
+	writeln!(markdown, "These results don't match the reference checksum, so their timings aren't comparable.\n").unwrap();
+	markdown.push_str("| Runtime | Kernel | Result | Expected | Reference |\n|---|---|---|---|---|\n");
+
+	for record in mismatched
+	{
+		let (expected, reference) = record.mismatch.as_ref().unwrap();
+		writeln!(markdown, "| {} | {} | {} | {} | {} |", record.implementation, record.kernel, record.checksum, expected, reference).unwrap();
+	}
+
+	markdown.push('\n');
+}
+
+fn write_legend(markdown: &mut String, records: &[ResultRecord])
+{
+	markdown.push_str("The bar legend items are as follows:\n<ul>\n");
+
+	for implementation in implementation_order(records)
+	{
+		match describe_implementation(&implementation)
+		{
+			Some(description) => writeln!(markdown, "  <li><strong>{}:</strong> {}</li>", implementation, description).unwrap(),
+			None => writeln!(markdown, "  <li><strong>{}</strong></li>", implementation).unwrap()
+		}
+	}
+
+	markdown.push_str("</ul>\n\n");
+}
+
+// Kernels are grouped the same way as the library's i32_benchmarks, f32_benchmarks and
+// f64_benchmarks modules.
+fn write_image_grid(markdown: &mut String, records: &[ResultRecord])
+{
+	let kernels: Vec<String> = kernel_order(records);
+	let sections: [(&str, Option<Category>); 4] = [
+		("Integer math", Some(Category::I32)),
+		("Single-precision math", Some(Category::F32)),
+		("Double-precision math", Some(Category::F64)),
+		("Other", None)
+	];
+
+	for (title, category) in sections
+	{
+		let members: Vec<&String> = kernels
+			.iter()
+			.filter(|kernel| Kernel::from_name(kernel).map(Kernel::category) == category)
+			.collect();
+
+		if (members.is_empty())
+		{
+			continue;
+		}
+
+		writeln!(markdown, "## {}\n\n<p align=\"center\"> ", title).unwrap();
+		for kernel in members
+		{
+			writeln!(markdown, "  <img src=\"img/{}.svg\" alt=\"{}\">", chart_name(kernel), kernel).unwrap();
+		}
+		markdown.push_str("</p>\n\n");
+	}
+}
+
+pub(crate) fn render_report(records: &[ResultRecord], environment: Option<&JsonValue>, timestamp: u64) -> String
+{
+	let mut markdown: String = String::new();
+
+	writeln!(markdown, "# Benchmark Results - {}\n", format_long_date(timestamp)).unwrap();
+	write_environment_section(&mut markdown, environment);
+	write_mismatches(&mut markdown, records);
+	write_legend(&mut markdown, records);
+	write_image_grid(&mut markdown, records);
+
+	return String::from(markdown.trim_end()) + "\n";
+}
+
+pub(crate) fn write_report(outputDirectory: &Path, records: &[ResultRecord], environment: Option<&JsonValue>, timestamp: u64) -> io::Result<()>
+{
+	write_charts(records, &outputDirectory.join("img"))?;
+
+	return fs::write(outputDirectory.join("README.md"), render_report(records, environment, timestamp));
+}
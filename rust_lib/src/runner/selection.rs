@@ -0,0 +1,80 @@
+// Picks kernels for --kernel and --exclude. A pattern is a category ("i32", "f32" or "f64"), or a
+// kernel name that may contain * and ? wildcards. Names match the Results.txt name ("Sieve of
+// Eratosthenes"), the workload table name ("sieve_of_eratosthenes") or the exported symbol
+// ("benchmark_sieve_of_eratosthenes"), ignoring case.
+
+use crate::kernels::{allKernels, Kernel};
+
+// Glob match where * is any run of characters and ? is any single character.
+fn glob_match(pattern: &[char], text: &[char]) -> bool
+{
+	let mut p: usize = 0;
+	let mut t: usize = 0;
+	// Where to resume after the last *, as (pattern index, text index).
+	let mut backtrack: Option<(usize, usize)> = None;
+
+	while (t < text.len())
+	{
+		if (p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]))
+		{
+			p += 1;
+			t += 1;
+		}
+		else if (p < pattern.len() && pattern[p] == '*')
+		{
+			backtrack = Some((p, t));
+			p += 1;
+		}
+		else if let Some((starP, starT)) = backtrack
+		{
+			p = starP + 1;
+			t = starT + 1;
+			backtrack = Some((starP, starT + 1));
+		}
+		else
+		{
+			return false;
+		}
+	}
+
+	return pattern[p..].iter().all(|character| *character == '*');
+}
+
+pub(crate) fn matches(pattern: &str, kernel: Kernel) -> bool
+{
+	let pattern: String = pattern.to_lowercase();
+
+	if (pattern == kernel.category().as_str())
+	{
+		return true;
+	}
+
+	let patternChars: Vec<char> = pattern.chars().collect();
+
+	return [kernel.name(), kernel.config_name(), kernel.symbol()]
+		.iter()
+		.any(|name| glob_match(&patternChars, &name.to_lowercase().chars().collect::<Vec<char>>()));
+}
+
+// Whether a kernel should run. With no --kernel patterns every kernel is included.
+pub(crate) fn is_selected(kernel: Kernel, include: &[String], exclude: &[String]) -> bool
+{
+	let included: bool = include.is_empty() || include.iter().any(|pattern| matches(pattern, kernel));
+
+	return included && !exclude.iter().any(|pattern| matches(pattern, kernel));
+}
+
+// Rejects patterns that match no kernel at all, since they are almost always typos.
+pub(crate) fn check_patterns(patterns: &[String]) -> Result<(), String>
+{
+	for pattern in patterns
+	{
+		if (!allKernels.iter().any(|kernel| matches(pattern, *kernel)))
+		{
+			let known: Vec<&str> = allKernels.iter().map(|kernel| kernel.config_name()).collect();
+			return Err(format!("'{}' matches no kernel. Kernels are {}, or one of the categories i32, f32 and f64", pattern, known.join(", ")));
+		}
+	}
+
+	return Ok(());
+}
@@ -0,0 +1,246 @@
+// Exports results in the layout of Benchmark Graph Template.ods: one sheet per kernel, "Runtime" and
+// "Ticks" headers in B3:C3 and one row per runtime from row 4. The template's charts read B4:C11, so
+// its eight runtimes always get a row, even when a result file has nothing for them.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+use crate::chart::{escape_xml, implementation_order, runtime_name};
+use crate::kernels::{allKernels, Category};
+use crate::output::{csv_field, ResultRecord};
+
+// Rows 4 to 11 of every sheet in the template.
+const templateRuntimes: [&str; 8] = [
+	"GCC",
+	"Clang",
+	"Burst",
+	"GCC – Unity",
+	"Clang – Unity",
+	"IL2CPP",
+	"Mono JIT",
+	"Ryu JIT"
+];
+
+// Sheets follow the template: integer kernels, then single-precision, then double-precision.
+fn sheet_order(records: &[ResultRecord]) -> Vec<String>
+{
+	let mut sheets: Vec<String> = Vec::new();
+
+	for category in [Category::I32, Category::F32, Category::F64]
+	{
+		for kernel in allKernels.iter().filter(|kernel| kernel.category() == category)
+		{
+			sheets.push(String::from(kernel.name()));
+		}
+	}
+
+	for record in records
+	{
+		if (!sheets.contains(&record.kernel))
+		{
+			sheets.push(record.kernel.clone());
+		}
+	}
+
+	return sheets;
+}
+
+// The template's runtimes followed by any others found in the results, such as Rust.
+fn runtime_order(records: &[ResultRecord]) -> Vec<String>
+{
+	let mut runtimes: Vec<String> = templateRuntimes.iter().map(|runtime| String::from(*runtime)).collect();
+
+	for implementation in implementation_order(records)
+	{
+		let runtime: String = runtime_name(&implementation);
+		if (!runtimes.contains(&runtime))
+		{
+			runtimes.push(runtime);
+		}
+	}
+
+	return runtimes;
+}
+
+fn ticks_for(records: &[ResultRecord], kernel: &str, runtime: &str) -> Option<f64>
+{
+	return records
+		.iter()
+		.find(|record| record.kernel == kernel && runtime_name(&record.implementation) == runtime)
+		.map(ResultRecord::median);
+}
+
+fn string_cell(text: &str) -> String
+{
+	return format!("<table:table-cell office:value-type=\"string\"><text:p>{}</text:p></table:table-cell>", escape_xml(text));
+}
+
+fn content_xml(records: &[ResultRecord]) -> String
+{
+	let runtimes: Vec<String> = runtime_order(records);
+	let mut xml: String = String::from(concat!(
+		"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+		"<office:document-content",
+		" xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\"",
+		" xmlns:table=\"urn:oasis:names:tc:opendocument:xmlns:table:1.0\"",
+		" xmlns:text=\"urn:oasis:names:tc:opendocument:xmlns:text:1.0\"",
+		" office:version=\"1.2\">\n",
+		"<office:body>\n<office:spreadsheet>\n"
+	));
+
+	for sheet in sheet_order(records)
+	{
+		writeln!(xml, "<table:table table:name=\"{}\">", escape_xml(&sheet)).unwrap();
+		xml.push_str("<table:table-column table:number-columns-repeated=\"3\"/>\n");
+		xml.push_str("<table:table-row table:number-rows-repeated=\"2\"><table:table-cell table:number-columns-repeated=\"3\"/></table:table-row>\n");
+		writeln!(xml, "<table:table-row><table:table-cell/>{}{}</table:table-row>", string_cell("Runtime"), string_cell("Ticks")).unwrap();
+
+		for runtime in &runtimes
+		{
+			let ticks: String = match ticks_for(records, &sheet, runtime)
+			{
+				Some(value) => format!("<table:table-cell office:value-type=\"float\" office:value=\"{:.0}\"><text:p>{:.0}</text:p></table:table-cell>", value, value),
+				None => String::from("<table:table-cell/>")
+			};
+
+			writeln!(xml, "<table:table-row><table:table-cell/>{}{}</table:table-row>", string_cell(runtime), ticks).unwrap();
+		}
+
+		xml.push_str("</table:table>\n");
+	}
+
+	xml.push_str("</office:spreadsheet>\n</office:body>\n</office:document-content>\n");
+
+	return xml;
+}
+
+const manifestXml: &str = concat!(
+	"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+	"<manifest:manifest xmlns:manifest=\"urn:oasis:names:tc:opendocument:xmlns:manifest:1.0\" manifest:version=\"1.2\">\n",
+	" <manifest:file-entry manifest:full-path=\"/\" manifest:version=\"1.2\" manifest:media-type=\"application/vnd.oasis.opendocument.spreadsheet\"/>\n",
+	" <manifest:file-entry manifest:full-path=\"content.xml\" manifest:media-type=\"text/xml\"/>\n",
+	"</manifest:manifest>\n"
+);
+
+fn crc32(data: &[u8]) -> u32
+{
+	let mut crc: u32 = 0xFFFFFFFF;
+
+	for byte in data
+	{
+		crc ^= *byte as u32;
+		for _ in 0..8
+		{
+			crc = if (crc & 1 == 1) { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+		}
+	}
+
+	return !crc;
+}
+
+// Writes an uncompressed zip archive. ODS requires "mimetype" to be the first entry and stored, and
+// storing everything keeps this simple.
+fn zip_stored(entries: &[(&str, &[u8])]) -> Vec<u8>
+{
+	// 1980-01-01 00:00 in MS-DOS format.
+	const dosTime: u16 = 0;
+	const dosDate: u16 = 0x21;
+
+	let mut archive: Vec<u8> = Vec::new();
+	let mut directory: Vec<u8> = Vec::new();
+
+	for (name, data) in entries
+	{
+		let offset: u32 = archive.len() as u32;
+		let crc: u32 = crc32(data);
+		let size: u32 = data.len() as u32;
+
+		archive.extend_from_slice(&0x04034b50u32.to_le_bytes());
+		archive.extend_from_slice(&20u16.to_le_bytes());
+		archive.extend_from_slice(&0u16.to_le_bytes());
+		archive.extend_from_slice(&0u16.to_le_bytes());
+		archive.extend_from_slice(&dosTime.to_le_bytes());
+		archive.extend_from_slice(&dosDate.to_le_bytes());
+		archive.extend_from_slice(&crc.to_le_bytes());
+		archive.extend_from_slice(&size.to_le_bytes());
+		archive.extend_from_slice(&size.to_le_bytes());
+		archive.extend_from_slice(&(name.len() as u16).to_le_bytes());
+		archive.extend_from_slice(&0u16.to_le_bytes());
+		archive.extend_from_slice(name.as_bytes());
+		archive.extend_from_slice(data);
+
+		directory.extend_from_slice(&0x02014b50u32.to_le_bytes());
+		directory.extend_from_slice(&20u16.to_le_bytes());
+		directory.extend_from_slice(&20u16.to_le_bytes());
+		directory.extend_from_slice(&0u16.to_le_bytes());
+		directory.extend_from_slice(&0u16.to_le_bytes());
+		directory.extend_from_slice(&dosTime.to_le_bytes());
+		directory.extend_from_slice(&dosDate.to_le_bytes());
+		directory.extend_from_slice(&crc.to_le_bytes());
+		directory.extend_from_slice(&size.to_le_bytes());
+		directory.extend_from_slice(&size.to_le_bytes());
+		directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+		directory.extend_from_slice(&[0; 12]);
+		directory.extend_from_slice(&offset.to_le_bytes());
+		directory.extend_from_slice(name.as_bytes());
+	}
+
+	let directoryOffset: u32 = archive.len() as u32;
+	let directorySize: u32 = directory.len() as u32;
+	archive.extend_from_slice(&directory);
+
+	archive.extend_from_slice(&0x06054b50u32.to_le_bytes());
+	archive.extend_from_slice(&[0; 4]);
+	archive.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+	archive.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+	archive.extend_from_slice(&directorySize.to_le_bytes());
+	archive.extend_from_slice(&directoryOffset.to_le_bytes());
+	archive.extend_from_slice(&0u16.to_le_bytes());
+
+	return archive;
+}
+
+pub(crate) fn write_ods(path: &str, records: &[ResultRecord]) -> io::Result<()>
+{
+	let content: String = content_xml(records);
+	let archive: Vec<u8> = zip_stored(&[
+		("mimetype", b"application/vnd.oasis.opendocument.spreadsheet"),
+		("META-INF/manifest.xml", manifestXml.as_bytes()),
+		("content.xml", content.as_bytes())
+	]);
+
+	return fs::write(path, archive);
+}
+
+// CSV can't hold sheets, so each sheet becomes a column, in sheet order, and each runtime a row, in
+// the template's row order.
+pub(crate) fn write_spreadsheet_csv(path: &str, records: &[ResultRecord]) -> io::Result<()>
+{
+	let sheets: Vec<String> = sheet_order(records);
+	let mut output: String = String::from("Runtime");
+
+	for sheet in &sheets
+	{
+		write!(output, ",{}", csv_field(sheet)).unwrap();
+	}
+	output.push('\n');
+
+	for runtime in runtime_order(records)
+	{
+		output.push_str(&csv_field(&runtime));
+
+		for sheet in &sheets
+		{
+			match ticks_for(records, sheet, &runtime)
+			{
+				Some(value) => write!(output, ",{:.0}", value).unwrap(),
+				None => output.push(',')
+			}
+		}
+
+		output.push('\n');
+	}
+
+	return fs::write(path, output);
+}
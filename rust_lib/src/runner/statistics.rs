@@ -0,0 +1,394 @@
+// Summary statistics over the timing samples of one kernel. All values are in the same unit as the
+// samples (nanoseconds).
+
+const bootstrapResamples: usize = 1000;
+const confidenceLevel: f64 = 0.95;
+
+// Samples whose modified z-score is above this are treated as outliers (Iglewicz and Hoaglin).
+const outlierThreshold: f64 = 3.5;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Statistics
+{
+	pub(crate) samples: usize,
+	pub(crate) outliers: usize,
+	pub(crate) min: f64,
+	pub(crate) median: f64,
+	pub(crate) mean: f64,
+	pub(crate) standardDeviation: f64,
+	pub(crate) medianAbsoluteDeviation: f64,
+	pub(crate) confidenceLow: f64,
+	pub(crate) confidenceHigh: f64
+}
+
+// Small xorshift generator so that the bootstrap and random kernel orders are reproducible between
+// runs.
+pub(crate) struct XorShift
+{
+	state: u64
+}
+
+impl XorShift
+{
+	// Scrambles the seed with the splitmix64 finaliser, since xorshift needs a non-zero state and its
+	// first outputs from small seeds are poorly mixed.
+	pub(crate) fn from_seed(seed: u64) -> XorShift
+	{
+		let mut state: u64 = seed.wrapping_add(0x9E3779B97F4A7C15);
+		state = (state ^ (state >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+		state = (state ^ (state >> 27)).wrapping_mul(0x94D049BB133111EB);
+		state ^= state >> 31;
+
+		return XorShift { state: if (state == 0) { 0x9E3779B97F4A7C15 } else { state } };
+	}
+
+	fn next(&mut self) -> u64
+	{
+		self.state ^= self.state << 13;
+		self.state ^= self.state >> 7;
+		self.state ^= self.state << 17;
+
+		return self.state;
+	}
+
+	pub(crate) fn below(&mut self, bound: usize) -> usize
+	{
+		return (self.next() % bound as u64) as usize;
+	}
+}
+
+pub(crate) fn median(sorted: &[f64]) -> f64
+{
+	let length: usize = sorted.len();
+
+	if (length == 0)
+	{
+		return f64::NAN;
+	}
+
+	if (length % 2 == 1)
+	{
+		return sorted[length / 2];
+	}
+
+	return (sorted[length / 2 - 1] + sorted[length / 2]) / 2.0;
+}
+
+fn sorted_copy(values: &[f64]) -> Vec<f64>
+{
+	let mut sorted: Vec<f64> = values.to_vec();
+	sorted.sort_by(f64::total_cmp);
+
+	return sorted;
+}
+
+fn median_absolute_deviation(sorted: &[f64], center: f64) -> f64
+{
+	let deviations: Vec<f64> = sorted_copy(&sorted.iter().map(|value| (value - center).abs()).collect::<Vec<f64>>());
+
+	return median(&deviations);
+}
+
+// Drops samples that are far from the median. The scale factor makes the MAD comparable to a
+// standard deviation for normally distributed data.
+pub(crate) fn reject_outliers(samples: &[f64]) -> Vec<f64>
+{
+	let sorted: Vec<f64> = sorted_copy(samples);
+	let center: f64 = median(&sorted);
+	let mad: f64 = median_absolute_deviation(&sorted, center);
+
+	if (mad == 0.0)
+	{
+		return sorted;
+	}
+
+	return sorted
+		.into_iter()
+		.filter(|value| 0.6745 * (value - center).abs() / mad <= outlierThreshold)
+		.collect();
+}
+
+// Percentile bootstrap of the median.
+fn bootstrap_median(sorted: &[f64]) -> (f64, f64)
+{
+	if (sorted.len() < 2)
+	{
+		let value: f64 = median(sorted);
+		return (value, value);
+	}
+
+	let mut random: XorShift = XorShift { state: 0x9E3779B97F4A7C15 };
+	let mut medians: Vec<f64> = Vec::with_capacity(bootstrapResamples);
+	let mut resample: Vec<f64> = vec![0.0; sorted.len()];
+
+	for _ in 0..bootstrapResamples
+	{
+		for value in resample.iter_mut()
+		{
+			*value = sorted[random.below(sorted.len())];
+		}
+
+		resample.sort_by(f64::total_cmp);
+		medians.push(median(&resample));
+	}
+
+	medians.sort_by(f64::total_cmp);
+
+	let tail: f64 = (1.0 - confidenceLevel) / 2.0;
+	let low: usize = (tail * bootstrapResamples as f64) as usize;
+	let high: usize = ((1.0 - tail) * bootstrapResamples as f64) as usize - 1;
+
+	return (medians[low], medians[high]);
+}
+
+pub(crate) fn compute_statistics(samples: &[f64]) -> Statistics
+{
+	let kept: Vec<f64> = reject_outliers(samples);
+
+	if (kept.is_empty())
+	{
+		return Statistics::default();
+	}
+
+	let count: f64 = kept.len() as f64;
+	let mean: f64 = kept.iter().sum::<f64>() / count;
+	let variance: f64 = if (kept.len() > 1)
+	{
+		kept.iter().map(|value| (value - mean) * (value - mean)).sum::<f64>() / (count - 1.0)
+	}
+	else
+	{
+		0.0
+	};
+	let center: f64 = median(&kept);
+	let (confidenceLow, confidenceHigh) = bootstrap_median(&kept);
+
+	return Statistics
+	{
+		samples: kept.len(),
+		outliers: samples.len() - kept.len(),
+		min: kept[0],
+		median: center,
+		mean,
+		standardDeviation: variance.sqrt(),
+		medianAbsoluteDeviation: median_absolute_deviation(&kept, center),
+		confidenceLow,
+		confidenceHigh
+	};
+}
+
+// Ranks of the combined samples, with ties given the average of the ranks they span.
+fn average_ranks(values: &[f64]) -> (Vec<f64>, bool)
+{
+	let mut order: Vec<usize> = (0..values.len()).collect();
+	order.sort_by(|left, right| values[*left].total_cmp(&values[*right]));
+
+	let mut ranks: Vec<f64> = vec![0.0; values.len()];
+	let mut hasTies: bool = false;
+	let mut i: usize = 0;
+
+	while (i < order.len())
+	{
+		let mut j: usize = i;
+		while (j + 1 < order.len() && values[order[j + 1]] == values[order[i]])
+		{
+			j += 1;
+		}
+
+		if (j > i)
+		{
+			hasTies = true;
+		}
+
+		let rank: f64 = (i + j) as f64 / 2.0 + 1.0;
+		for k in i..=j
+		{
+			ranks[order[k]] = rank;
+		}
+
+		i = j + 1;
+	}
+
+	return (ranks, hasTies);
+}
+
+fn binomial(n: usize, k: usize) -> f64
+{
+	let mut result: f64 = 1.0;
+
+	for i in 0..k
+	{
+		result = result * (n - i) as f64 / (i + 1) as f64;
+	}
+
+	return result;
+}
+
+// The smallest two-sided p-value the Mann-Whitney U test can produce for these sample sizes. If it is
+// above the significance level the test can never reject, so callers should not rely on it.
+pub(crate) fn minimum_p_value(leftCount: usize, rightCount: usize) -> f64
+{
+	return f64::min(1.0, 2.0 / binomial(leftCount + rightCount, leftCount));
+}
+
+// Number of orderings of the two samples for every value of U, without ties.
+fn exact_u_distribution(leftCount: usize, rightCount: usize) -> Vec<f64>
+{
+	let maximum: usize = leftCount * rightCount;
+
+	// counts[n][u] for the current m, built up one left sample at a time.
+	let mut counts: Vec<Vec<f64>> = (0..=rightCount)
+		.map(|_| {
+			let mut row: Vec<f64> = vec![0.0; maximum + 1];
+			row[0] = 1.0;
+			row
+		})
+		.collect();
+
+	for m in 1..=leftCount
+	{
+		let mut next: Vec<Vec<f64>> = vec![vec![0.0; maximum + 1]; rightCount + 1];
+		next[0][0] = 1.0;
+
+		for n in 1..=rightCount
+		{
+			for u in 0..=(m * n)
+			{
+				let mut value: f64 = next[n - 1][u];
+				if (u >= n)
+				{
+					value += counts[n][u - n];
+				}
+				next[n][u] = value;
+			}
+		}
+
+		counts = next;
+	}
+
+	return counts.swap_remove(rightCount);
+}
+
+fn normal_cdf(x: f64) -> f64
+{
+	// Abramowitz and Stegun 7.1.26, accurate to about 1e-7.
+	let t: f64 = 1.0 / (1.0 + 0.3275911 * x.abs() / std::f64::consts::SQRT_2);
+	let polynomial: f64 = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+	let erf: f64 = 1.0 - polynomial * (-(x * x) / 2.0).exp();
+
+	return if (x >= 0.0) { (1.0 + erf) / 2.0 } else { (1.0 - erf) / 2.0 };
+}
+
+// Two-sided p-value of the Mann-Whitney U test. Small samples without ties use the exact
+// distribution, everything else the normal approximation with tie and continuity corrections.
+pub(crate) fn mann_whitney_u(left: &[f64], right: &[f64]) -> Option<f64>
+{
+	if (left.is_empty() || right.is_empty())
+	{
+		return None;
+	}
+
+	let leftCount: usize = left.len();
+	let rightCount: usize = right.len();
+	let combined: Vec<f64> = left.iter().chain(right.iter()).copied().collect();
+	let (ranks, hasTies) = average_ranks(&combined);
+
+	let leftRankSum: f64 = ranks[..leftCount].iter().sum();
+	let leftU: f64 = leftRankSum - (leftCount * (leftCount + 1)) as f64 / 2.0;
+	let product: f64 = (leftCount * rightCount) as f64;
+	let smallerU: f64 = f64::min(leftU, product - leftU);
+
+	if (!hasTies && leftCount * rightCount <= 400)
+	{
+		let distribution: Vec<f64> = exact_u_distribution(leftCount, rightCount);
+		let total: f64 = distribution.iter().sum();
+		let tail: f64 = distribution[..=(smallerU as usize)].iter().sum();
+
+		return Some(f64::min(1.0, 2.0 * tail / total));
+	}
+
+	let count: f64 = (leftCount + rightCount) as f64;
+	let mut tieTerm: f64 = 0.0;
+	let mut sortedRanks: Vec<f64> = ranks.clone();
+	sortedRanks.sort_by(f64::total_cmp);
+
+	let mut i: usize = 0;
+	while (i < sortedRanks.len())
+	{
+		let mut j: usize = i;
+		while (j + 1 < sortedRanks.len() && sortedRanks[j + 1] == sortedRanks[i])
+		{
+			j += 1;
+		}
+
+		let tied: f64 = (j - i + 1) as f64;
+		tieTerm += tied * tied * tied - tied;
+		i = j + 1;
+	}
+
+	let variance: f64 = product / 12.0 * ((count + 1.0) - tieTerm / (count * (count - 1.0)));
+	if (variance <= 0.0)
+	{
+		return Some(1.0);
+	}
+
+	let z: f64 = (f64::abs(leftU - product / 2.0) - 0.5).max(0.0) / variance.sqrt();
+
+	return Some(f64::min(1.0, 2.0 * (1.0 - normal_cdf(z))));
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn exact_distribution_counts_orderings()
+	{
+		// The six orderings of two samples against two give U = 0, 1, 2, 2, 3, 4.
+		assert_eq!(exact_u_distribution(2, 2), vec![1.0, 1.0, 2.0, 1.0, 1.0]);
+	}
+
+	#[test]
+	fn separated_samples_use_the_exact_distribution()
+	{
+		// U = 0 is one of the 20 orderings, and the test is two-sided.
+		let p: f64 = mann_whitney_u(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]).unwrap();
+
+		assert!((p - 0.1).abs() < 1e-12, "p = {}", p);
+	}
+
+	#[test]
+	fn identical_samples_are_not_different()
+	{
+		// Ties across the samples give z = 0, and normal_cdf is only accurate to about 1e-7.
+		let p: f64 = mann_whitney_u(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]).unwrap();
+		assert!((p - 1.0).abs() < 1e-7, "p = {}", p);
+
+		// With every value tied the variance is zero.
+		assert_eq!(mann_whitney_u(&[5.0, 5.0, 5.0], &[5.0, 5.0, 5.0]), Some(1.0));
+	}
+
+	#[test]
+	fn ties_use_the_normal_approximation()
+	{
+		// Ranks 1, 2.5, 2.5, 4.5 against 4.5, 6, 7 give U = 0.5. The variance is 12 / 12 * (8 - 12 / 42)
+		// and z = (6 - 0.5 - 0.5) / sqrt(variance) = 1.8002.
+		let p: f64 = mann_whitney_u(&[1.0, 2.0, 2.0, 3.0], &[3.0, 4.0, 5.0]).unwrap();
+
+		assert!((p - 0.071828).abs() < 1e-5, "p = {}", p);
+	}
+
+	#[test]
+	fn empty_samples_have_no_p_value()
+	{
+		assert_eq!(mann_whitney_u(&[], &[1.0]), None);
+	}
+
+	#[test]
+	fn minimum_p_value_of_tiny_samples()
+	{
+		assert_eq!(minimum_p_value(1, 1), 1.0);
+		assert!((minimum_p_value(3, 3) - 0.1).abs() < 1e-12);
+	}
+}
@@ -0,0 +1,202 @@
+// --sweep runs one kernel over a grid of parameter values instead of a single workload, e.g.
+// Mandelbrot's width and height or the number of Fireflies boids, and reports the time per unit of
+// work at every point. A flat curve means the kernel scales linearly. Steps show where the working set
+// falls out of a cache level, and a steady climb shows superlinear work such as Fireflies' neighbour
+// loops.
+
+use std::fs;
+use std::io;
+
+use crate::chart::render_line_chart;
+use crate::diff::format_table;
+use crate::kernels::Kernel;
+use crate::measurement::Measurement;
+
+// More points than this is almost certainly a mistake in a range.
+const maximumPoints: usize = 10000;
+
+// A list such as "100,200,400", a linear range "100..1000:100" or a geometric range "64..4096*2". Both
+// ends of a range are included.
+fn parse_values(text: &str) -> Result<Vec<u32>, String>
+{
+	let parse = |value: &str| value.trim().replace('_', "").parse::<u32>().map_err(|_| format!("'{}' is not a whole number", value.trim()));
+
+	let Some((start, rest)) = text.split_once("..") else
+	{
+		return text.split(',').map(parse).collect();
+	};
+
+	let start: u32 = parse(start)?;
+	let (end, step, geometric) = if let Some((end, factor)) = rest.split_once('*')
+	{
+		(parse(end)?, parse(factor)?, true)
+	}
+	else if let Some((end, step)) = rest.split_once(':')
+	{
+		(parse(end)?, parse(step)?, false)
+	}
+	else
+	{
+		return Err(format!("The range '{}' needs a step, e.g. {}:1 or {}*2", text, text, text));
+	};
+
+	if ((geometric && (step < 2 || start == 0)) || (!geometric && step == 0) || end < start)
+	{
+		return Err(format!("The range '{}' never reaches its end", text));
+	}
+
+	let mut values: Vec<u32> = Vec::new();
+	let mut value: u64 = start as u64;
+	while (value <= end as u64 && values.len() <= maximumPoints)
+	{
+		values.push(value as u32);
+		value = if (geometric) { value * step as u64 } else { value + step as u64 };
+	}
+
+	return Ok(values);
+}
+
+// Nanoseconds per unit of work, or per run for Fibonacci, which has no unit.
+fn time_per_point(measurement: &Measurement) -> f64
+{
+	return measurement.time_per_work().map(|(time, _)| time).unwrap_or(measurement.statistics.median);
+}
+
+fn work_name(kernel: Kernel, parameters: &[u32]) -> &'static str
+{
+	return kernel.work_amount(parameters).map(|(_, unit)| unit).unwrap_or("run");
+}
+
+// "<parameter>=<values>" for --sweep.
+pub(crate) fn parse_sweep(text: &str) -> Result<(String, Vec<u32>), String>
+{
+	let (name, values) = text.split_once('=').ok_or_else(|| format!("--sweep expects <parameter>=<values>, got '{}'", text))?;
+	let values: Vec<u32> = parse_values(values).map_err(|message| format!("--sweep {}: {}", name, message))?;
+
+	return Ok((String::from(name.trim()), values));
+}
+
+// Every combination of the swept values, with the first --sweep varying slowest, applied on top of
+// the kernel's parameters from the workload file.
+pub(crate) fn sweep_grid(kernel: Kernel, base: &[u32], sweeps: &[(String, Vec<u32>)]) -> Result<Vec<Vec<u32>>, String>
+{
+	let names: &[&str] = kernel.parameter_names();
+	let mut grid: Vec<Vec<u32>> = vec![base.to_vec()];
+
+	for (name, values) in sweeps
+	{
+		let index: usize = names
+			.iter()
+			.position(|candidate| candidate == name)
+			.ok_or_else(|| format!("{} has no parameter '{}'. Its parameters are {}", kernel.name(), name, names.join(", ")))?;
+
+		if (sweeps.iter().filter(|(other, _)| other == name).count() > 1)
+		{
+			return Err(format!("--sweep {} is given more than once", name));
+		}
+
+		let minimum: u32 = kernel.parameter_minimums()[index];
+		if let Some(value) = values.iter().find(|value| **value < minimum)
+		{
+			return Err(format!("{} of {} can't be below {}, got {}", name, kernel.name(), minimum, value));
+		}
+
+		grid = grid
+			.iter()
+			.flat_map(|parameters| values.iter().map(move |value| {
+				let mut point: Vec<u32> = parameters.clone();
+				point[index] = *value;
+				point
+			}))
+			.collect();
+
+		if (grid.len() > maximumPoints)
+		{
+			return Err(format!("The sweep has more than {} points", maximumPoints));
+		}
+	}
+
+	return Ok(grid);
+}
+
+// One row per point and implementation: the swept values, the median, the time per unit of work and
+// that time relative to the implementation's first point.
+pub(crate) fn format_sweep(measurements: &[Measurement], sweeps: &[(String, Vec<u32>)]) -> Vec<String>
+{
+	let Some(first) = measurements.first() else
+	{
+		return Vec::new();
+	};
+
+	let names: &[&str] = first.kernel.parameter_names();
+	let indices: Vec<usize> = sweeps.iter().filter_map(|(name, _)| names.iter().position(|candidate| candidate == name)).collect();
+	let workName: &str = work_name(first.kernel, &first.parameters);
+
+	let mut header: Vec<String> = vec![String::from("Implementation")];
+	header.extend(indices.iter().map(|index| String::from(names[*index])));
+	header.extend([String::from("Median"), format!("ns per {}", workName), String::from("Relative")]);
+	let mut rows: Vec<Vec<String>> = vec![header];
+
+	for measurement in measurements
+	{
+		let time: f64 = time_per_point(measurement);
+		let firstTime: f64 = measurements
+			.iter()
+			.find(|other| other.implementation == measurement.implementation)
+			.map(time_per_point)
+			.unwrap_or(f64::NAN);
+
+		let mut row: Vec<String> = vec![measurement.implementation.clone()];
+		row.extend(indices.iter().map(|index| measurement.parameters[*index].to_string()));
+		row.push(format!("{:.0} ns", measurement.statistics.median));
+		row.push(format!("{:.3}", time));
+		row.push(if (firstTime > 0.0) { format!("{:.2}x", time / firstTime) } else { String::from("n/a") });
+		rows.push(row);
+	}
+
+	return format_table(&rows, 1);
+}
+
+// The chart of time per unit of work against the grid points, one line per implementation. Points are
+// labelled with their swept values, e.g. "640x360" when sweeping width and height.
+pub(crate) fn write_sweep_chart(path: &str, kernel: Kernel, grid: &[Vec<u32>], sweeps: &[(String, Vec<u32>)], measurements: &[Measurement]) -> io::Result<()>
+{
+	let names: &[&str] = kernel.parameter_names();
+	let indices: Vec<usize> = sweeps.iter().filter_map(|(name, _)| names.iter().position(|candidate| candidate == name)).collect();
+	let labels: Vec<String> = grid
+		.iter()
+		.map(|point| indices.iter().map(|index| point[*index].to_string()).collect::<Vec<String>>().join("x"))
+		.collect();
+
+	let mut implementations: Vec<&str> = Vec::new();
+	for measurement in measurements
+	{
+		if (!implementations.contains(&measurement.implementation.as_str()))
+		{
+			implementations.push(&measurement.implementation);
+		}
+	}
+
+	let series: Vec<(String, Vec<f64>)> = implementations
+		.iter()
+		.map(|implementation| {
+			let values: Vec<f64> = grid
+				.iter()
+				.map(|point| {
+					measurements
+						.iter()
+						.find(|measurement| measurement.implementation == *implementation && &measurement.parameters == point)
+						.map(time_per_point)
+						.unwrap_or(f64::NAN)
+				})
+				.collect();
+			(String::from(*implementation), values)
+		})
+		.collect();
+
+	let workName: &str = work_name(kernel, &grid[0]);
+	let swept: Vec<&str> = indices.iter().map(|index| names[*index]).collect();
+	let title: String = format!("{} by {}", kernel.name(), swept.join(" x "));
+
+	return fs::write(path, render_line_chart(&title, &format!("ns per {}", workName), &labels, &series));
+}
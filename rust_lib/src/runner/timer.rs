@@ -0,0 +1,209 @@
+// Clocks for the timed region. On x86-64 with an invariant TSC the runner counts TSC cycles, which
+// have far finer resolution than Stopwatch ticks, and converts them to nanoseconds with a frequency
+// calibrated against CLOCK_MONOTONIC. Everywhere else it uses Instant, which is CLOCK_MONOTONIC on
+// Linux.
+
+use std::time::{Duration, Instant};
+
+const calibrationRounds: usize = 5;
+const calibrationInterval: Duration = Duration::from_millis(20);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum TimerKind
+{
+	Tsc,
+	Instant
+}
+
+impl TimerKind
+{
+	pub(crate) fn from_name(name: &str) -> Option<TimerKind>
+	{
+		return match name
+		{
+			"tsc" => Some(TimerKind::Tsc),
+			"instant" => Some(TimerKind::Instant),
+			_ => None
+		};
+	}
+}
+
+pub(crate) enum Timer
+{
+	// TSC cycles, converted with the calibrated frequency in Hz.
+	Tsc { frequency: f64 },
+	// Nanoseconds since `origin`.
+	Monotonic { origin: Instant }
+}
+
+// CPUID leaf 0x80000007, EDX bit 8: the TSC ticks at a constant rate in every P-, C- and T-state, so
+// cycles convert to time with a single frequency. Leaf 0x80000001, EDX bit 27 is also required, since
+// read_tsc_end uses rdtscp and some hypervisors hide it even when the TSC is invariant.
+#[cfg(target_arch = "x86_64")]
+fn has_invariant_tsc() -> bool
+{
+	use std::arch::x86_64::__cpuid;
+
+	let highest: u32 = __cpuid(0x80000000).eax;
+	if (highest < 0x80000007)
+	{
+		return false;
+	}
+
+	let hasRdtscp: bool = __cpuid(0x80000001).edx & (1 << 27) != 0;
+
+	return hasRdtscp && __cpuid(0x80000007).edx & (1 << 8) != 0;
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn has_invariant_tsc() -> bool
+{
+	return false;
+}
+
+// lfence keeps earlier instructions from drifting past the read, and the read from being
+// reordered before them.
+#[cfg(target_arch = "x86_64")]
+fn read_tsc_start() -> u64
+{
+	use std::arch::x86_64::{_mm_lfence, _rdtsc};
+
+	unsafe
+	{
+		_mm_lfence();
+		let cycles: u64 = _rdtsc();
+		_mm_lfence();
+		return cycles;
+	}
+}
+
+// rdtscp waits for the timed code to finish, and the lfence keeps later instructions from starting
+// before the read.
+#[cfg(target_arch = "x86_64")]
+fn read_tsc_end() -> u64
+{
+	use std::arch::x86_64::{__rdtscp, _mm_lfence};
+
+	unsafe
+	{
+		let mut processor: u32 = 0;
+		let cycles: u64 = __rdtscp(&mut processor);
+		_mm_lfence();
+		return cycles;
+	}
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn read_tsc_start() -> u64
+{
+	unreachable!("the TSC timer is only created on x86-64");
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn read_tsc_end() -> u64
+{
+	unreachable!("the TSC timer is only created on x86-64");
+}
+
+// Median of a few short rounds. Each round brackets both clocks with TSC reads so that the time
+// spent reading Instant is split evenly.
+fn calibrate_tsc() -> f64
+{
+	let mut frequencies: Vec<f64> = Vec::with_capacity(calibrationRounds);
+
+	for _ in 0..calibrationRounds
+	{
+		let before: u64 = read_tsc_start();
+		let start: Instant = Instant::now();
+		let startCycles: u64 = (before + read_tsc_start()) / 2;
+
+		std::thread::sleep(calibrationInterval);
+
+		let before: u64 = read_tsc_start();
+		let elapsed: Duration = start.elapsed();
+		let endCycles: u64 = (before + read_tsc_start()) / 2;
+
+		frequencies.push((endCycles - startCycles) as f64 / elapsed.as_secs_f64());
+	}
+
+	frequencies.sort_by(f64::total_cmp);
+
+	return frequencies[calibrationRounds / 2];
+}
+
+impl Timer
+{
+	// The requested timer, or with no request the TSC if it is invariant and Instant otherwise.
+	pub(crate) fn create(requested: Option<TimerKind>) -> Result<Timer, String>
+	{
+		let useTsc: bool = match requested
+		{
+			Some(TimerKind::Tsc) if (!has_invariant_tsc()) => return Err(String::from("--timer tsc needs an x86-64 CPU with an invariant TSC and rdtscp")),
+			Some(TimerKind::Tsc) => true,
+			Some(TimerKind::Instant) => false,
+			None => has_invariant_tsc()
+		};
+
+		if (useTsc)
+		{
+			return Ok(Timer::Tsc { frequency: calibrate_tsc() });
+		}
+
+		return Ok(Timer::Monotonic { origin: Instant::now() });
+	}
+
+	pub(crate) fn name(&self) -> &'static str
+	{
+		return match self
+		{
+			Timer::Tsc { .. } => "tsc",
+			Timer::Monotonic { .. } => "instant"
+		};
+	}
+
+	pub(crate) fn tsc_frequency(&self) -> Option<f64>
+	{
+		return match self
+		{
+			Timer::Tsc { frequency } => Some(*frequency),
+			Timer::Monotonic { .. } => None
+		};
+	}
+
+	pub(crate) fn description(&self) -> String
+	{
+		return match self
+		{
+			Timer::Tsc { frequency } => format!("Timer: invariant TSC at {:.3} GHz, calibrated against CLOCK_MONOTONIC", frequency / 1e9),
+			Timer::Monotonic { .. } => String::from("Timer: Instant (CLOCK_MONOTONIC)")
+		};
+	}
+
+	pub(crate) fn start(&self) -> u64
+	{
+		return match self
+		{
+			Timer::Tsc { .. } => read_tsc_start(),
+			Timer::Monotonic { origin } => origin.elapsed().as_nanos() as u64
+		};
+	}
+
+	pub(crate) fn stop(&self) -> u64
+	{
+		return match self
+		{
+			Timer::Tsc { .. } => read_tsc_end(),
+			Timer::Monotonic { origin } => origin.elapsed().as_nanos() as u64
+		};
+	}
+
+	// Converts the difference of two readings to nanoseconds.
+	pub(crate) fn to_nanoseconds(&self, ticks: u64) -> f64
+	{
+		return match self
+		{
+			Timer::Tsc { frequency } => ticks as f64 * 1e9 / frequency,
+			Timer::Monotonic { .. } => ticks as f64
+		};
+	}
+}
@@ -0,0 +1,136 @@
+// Checks that every run computed the same result as the C reference, either by comparing with
+// another implementation of the same run (--reference) or with checksums recorded from
+// native_lib/benchmarks.c. A faster kernel that computes something else is not a result.
+
+use benchmarks_rust::golden::{find_golden, Divergence, GoldenChecksum, GoldenValue};
+
+use crate::implementation::linkedName;
+use crate::kernels::{Checksum, Kernel};
+use crate::measurement::Measurement;
+
+// Relative difference allowed between float checksums. Compilers may contract a * b + c into an FMA
+// where the target has one, which moves long reductions such as 100M NBody steps by about 3e-5.
+const floatTolerance: f64 = 1e-4;
+
+// The name used for the golden table in output.
+pub(crate) const goldenReference: &str = "golden";
+
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum Validation
+{
+	// Nothing to compare with: no golden checksum for these parameters and no --reference.
+	Unchecked,
+	Match { reference: String },
+	Mismatch { reference: String, expected: Checksum },
+	// The linked Rust kernel returned the value its port is known to return instead of C's.
+	KnownDivergence { reference: String, expected: Checksum, reason: &'static str }
+}
+
+impl Validation
+{
+	pub(crate) fn status(&self) -> &'static str
+	{
+		return match self
+		{
+			Validation::Unchecked => "unchecked",
+			Validation::Match { .. } => "match",
+			Validation::Mismatch { .. } => "mismatch",
+			Validation::KnownDivergence { .. } => "known_divergence"
+		};
+	}
+}
+
+fn checksum_from_golden(value: GoldenValue) -> Checksum
+{
+	return match value
+	{
+		GoldenValue::U32(value) => Checksum::U32(value),
+		GoldenValue::I32(value) => Checksum::I32(value),
+		GoldenValue::U64(value) => Checksum::U64(value),
+		GoldenValue::F32(value) => Checksum::F32(value),
+		GoldenValue::F64(value) => Checksum::F64(value)
+	};
+}
+
+pub(crate) fn golden_checksum(kernel: Kernel, parameters: &[u32]) -> Option<Checksum>
+{
+	return find_golden(kernel.symbol(), parameters).map(|golden| checksum_from_golden(golden.expected));
+}
+
+// How the linked Rust kernel is known to differ from C for these parameters, if it is.
+fn known_divergence(measurement: &Measurement) -> Option<&'static Divergence>
+{
+	if (measurement.implementation != linkedName)
+	{
+		return None;
+	}
+
+	let golden: &GoldenChecksum = find_golden(measurement.kernel.symbol(), &measurement.parameters)?;
+
+	return golden.rustDivergence.as_ref();
+}
+
+fn relatively_close(actual: f64, expected: f64) -> bool
+{
+	if (actual == expected || (actual.is_nan() && expected.is_nan()))
+	{
+		return true;
+	}
+
+	return (actual - expected).abs() <= floatTolerance * actual.abs().max(expected.abs());
+}
+
+// Integers must match exactly, floats within floatTolerance.
+pub(crate) fn checksums_agree(actual: Checksum, expected: Checksum) -> bool
+{
+	return match (actual, expected)
+	{
+		(Checksum::F32(actual), Checksum::F32(expected)) => relatively_close(actual as f64, expected as f64),
+		(Checksum::F64(actual), Checksum::F64(expected)) => relatively_close(actual, expected),
+		_ => actual == expected
+	};
+}
+
+// A known divergence only excuses the exact value the port is known to return, so any other change
+// is still a mismatch.
+fn compare(measurement: &Measurement, expected: Checksum, reference: &str) -> Validation
+{
+	if (checksums_agree(measurement.checksum, expected))
+	{
+		return Validation::Match { reference: String::from(reference) };
+	}
+
+	if let Some(divergence) = known_divergence(measurement)
+	{
+		if (checksums_agree(measurement.checksum, checksum_from_golden(divergence.value)))
+		{
+			return Validation::KnownDivergence { reference: String::from(reference), expected, reason: divergence.reason };
+		}
+	}
+
+	return Validation::Mismatch { reference: String::from(reference), expected };
+}
+
+// Validates every implementation's measurement of one kernel. With a reference implementation the
+// others are compared with its checksum, and the reference itself with the golden table.
+pub(crate) fn validate(measurements: &mut [Measurement], reference: Option<&str>)
+{
+	let referenceChecksum: Option<Checksum> = reference.and_then(|name| {
+		measurements.iter().find(|measurement| measurement.implementation == name).map(|measurement| measurement.checksum)
+	});
+
+	for measurement in measurements.iter_mut()
+	{
+		let isReference: bool = Some(measurement.implementation.as_str()) == reference;
+
+		measurement.validation = match (referenceChecksum, isReference)
+		{
+			(Some(expected), false) => compare(measurement, expected, reference.unwrap_or_default()),
+			_ => match golden_checksum(measurement.kernel, &measurement.parameters)
+			{
+				Some(expected) => compare(measurement, expected, goldenReference),
+				None => Validation::Unchecked
+			}
+		};
+	}
+}
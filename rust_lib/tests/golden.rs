@@ -0,0 +1,149 @@
+// Calls every exported kernel with small parameters and checks the value it returns against
+// native_lib/benchmarks.c built with gcc 12 -O3. The first set of parameters for each kernel is the
+// one in workloads/smoke.toml, and its value comes from the table in src/golden.rs that the runner
+// validates against.
+
+#![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
+#![allow(unused_parens)]
+#![allow(clippy::needless_return)]
+
+use benchmarks_rust::i32_benchmarks::{
+	benchmark_arcfour,
+	benchmark_fibonacci,
+	benchmark_radix,
+	benchmark_seahash,
+	benchmark_sieve_of_eratosthenes
+};
+use benchmarks_rust::f32_benchmarks::{
+	benchmark_fireflies_flocking,
+	benchmark_mandelbrot,
+	benchmark_particle_kinematics,
+	benchmark_pixar_raytracer,
+	benchmark_polynomials
+};
+use benchmarks_rust::f64_benchmarks::benchmark_nbody;
+use benchmarks_rust::golden::{find_golden, GoldenChecksum, GoldenValue};
+
+// What the Rust port should return for a workload in the golden table. Where the port is known to
+// diverge from C, that is the value recorded with the divergence, so any further change still fails.
+fn rust_expected(symbol: &str, parameters: &[u32]) -> GoldenValue
+{
+	let golden: &GoldenChecksum = find_golden(symbol, parameters).unwrap_or_else(|| panic!("no golden checksum for {} {:?}", symbol, parameters));
+
+	return match &golden.rustDivergence
+	{
+		Some(divergence) => divergence.value,
+		None => golden.expected
+	};
+}
+
+fn rust_expected_float(symbol: &str, parameters: &[u32]) -> f64
+{
+	return match rust_expected(symbol, parameters)
+	{
+		GoldenValue::F32(value) => value as f64,
+		GoldenValue::F64(value) => value,
+		other => panic!("{} {:?} has the integer checksum {:?}", symbol, parameters, other)
+	};
+}
+
+// Float kernels may drift from C in the last bits, e.g. where C's libm rounds differently, but
+// anything beyond this relative difference is a different computation.
+fn assert_close(actual: f64, expected: f64, tolerance: f64)
+{
+	let difference: f64 = (actual - expected).abs();
+
+	assert!(
+		difference <= tolerance * expected.abs().max(f64::MIN_POSITIVE),
+		"got {}, expected {} (relative difference {:e})",
+		actual,
+		expected,
+		difference / expected.abs()
+	);
+}
+
+const f32Tolerance: f64 = 1e-6;
+const f64Tolerance: f64 = 1e-12;
+
+#[test]
+fn fibonacci()
+{
+	assert_eq!(GoldenValue::U32(benchmark_fibonacci(25)), rust_expected("benchmark_fibonacci", &[25]));
+	assert_eq!(benchmark_fibonacci(10), 89);
+	assert_eq!(benchmark_fibonacci(1), 1);
+	assert_eq!(benchmark_fibonacci(0), 1);
+}
+
+#[test]
+fn sieve_of_eratosthenes()
+{
+	assert_eq!(GoldenValue::U32(benchmark_sieve_of_eratosthenes(100)), rust_expected("benchmark_sieve_of_eratosthenes", &[100]));
+	assert_eq!(benchmark_sieve_of_eratosthenes(1), 308);
+}
+
+#[test]
+fn arcfour()
+{
+	assert_eq!(GoldenValue::I32(benchmark_arcfour(1000)), rust_expected("benchmark_arcfour", &[1000]));
+	assert_eq!(benchmark_arcfour(1), 10);
+}
+
+#[test]
+fn seahash()
+{
+	assert_eq!(GoldenValue::U64(benchmark_seahash(100)), rust_expected("benchmark_seahash", &[100]));
+	assert_eq!(benchmark_seahash(1), 11468720252350332299);
+}
+
+// A known divergence: C returns 160 and 53 here, see radixDivergence in src/golden.rs.
+#[test]
+fn radix()
+{
+	assert_eq!(GoldenValue::I32(benchmark_radix(100)), rust_expected("benchmark_radix", &[100]));
+	assert_eq!(benchmark_radix(1), 0);
+}
+
+#[test]
+fn mandelbrot()
+{
+	assert_close(benchmark_mandelbrot(192, 108, 8) as f64, rust_expected_float("benchmark_mandelbrot", &[192, 108, 8]), f32Tolerance);
+	assert_close(benchmark_mandelbrot(64, 48, 4) as f64, 3.9217396, f32Tolerance);
+}
+
+#[test]
+fn pixar_raytracer()
+{
+	assert_close(benchmark_pixar_raytracer(72, 48, 1) as f64, rust_expected_float("benchmark_pixar_raytracer", &[72, 48, 1]), f32Tolerance);
+	assert_close(benchmark_pixar_raytracer(16, 12, 1) as f64, 762.17584, f32Tolerance);
+}
+
+#[test]
+fn fireflies_flocking()
+{
+	assert_close(benchmark_fireflies_flocking(100, 100) as f64, rust_expected_float("benchmark_fireflies_flocking", &[100, 100]), f32Tolerance);
+	assert_close(benchmark_fireflies_flocking(10, 10) as f64, 1879991170.0, f32Tolerance);
+}
+
+#[test]
+fn polynomials()
+{
+	assert_close(benchmark_polynomials(1000) as f64, rust_expected_float("benchmark_polynomials", &[1000]), f32Tolerance);
+	assert_close(benchmark_polynomials(10) as f64, 25.0, f32Tolerance);
+}
+
+#[test]
+fn particle_kinematics()
+{
+	assert_close(benchmark_particle_kinematics(100, 1000) as f64, rust_expected_float("benchmark_particle_kinematics", &[100, 1000]), f32Tolerance);
+	assert_close(benchmark_particle_kinematics(10, 10) as f64, 63.0, f32Tolerance);
+}
+
+// A known divergence: C returns 0.009135699168192925 and -0.002822175228967256 here, see
+// nbodyDivergence in src/golden.rs.
+#[test]
+fn nbody()
+{
+	assert_close(benchmark_nbody(10000), rust_expected_float("benchmark_nbody", &[10000]), f64Tolerance);
+	assert_close(benchmark_nbody(100), -0.002841086502523511, f64Tolerance);
+}